@@ -0,0 +1,133 @@
+use crate::Error;
+use serde_json::{json, Value};
+use std::env;
+
+/// An abstract in addition to the English `abstract` and the Swedish
+/// `sammandrag`, for instance a popular science summary or an abstract in
+/// the native language of an author
+pub(crate) struct Abstract {
+    /// A language tag such as "sv" or "de"
+    pub(crate) language: String,
+    pub(crate) heading: String,
+    pub(crate) keywords: Option<String>,
+    pub(crate) content: String,
+}
+
+/// Transform `[abstract]`, which adds an abstract to the front matter
+pub(crate) fn transform_abstract(input: Value, _to: &str) -> Result<String, Error> {
+    let arguments = &input["arguments"];
+
+    let entry = json!({
+        "language": arguments["language"].as_str().unwrap_or("en").trim(),
+        "heading": arguments["heading"].as_str().unwrap_or("Abstract").trim(),
+        "keywords": arguments["keywords"].as_str().unwrap_or_default().trim(),
+        "content": input["data"].as_str().unwrap_or_default(),
+    });
+
+    let json = json!([
+        {"name": "list-push", "arguments": {"name": "abstracts"}, "data": entry.to_string()}
+    ]);
+    Ok(serde_json::to_string(&json).unwrap())
+}
+
+impl Abstract {
+    pub(crate) fn read_all() -> Vec<Self> {
+        let var = env::var("abstracts").unwrap_or_else(|_| "[]".to_string());
+        let entries: Vec<String> = serde_json::from_str(&var).unwrap();
+
+        entries
+            .iter()
+            .map(|entry| {
+                let entry: Value = serde_json::from_str(entry).unwrap();
+                let get = |key: &str| entry[key].as_str().unwrap_or_default().to_string();
+
+                Self {
+                    language: get("language"),
+                    heading: get("heading"),
+                    keywords: Some(get("keywords")).filter(|keywords| !keywords.is_empty()),
+                    content: get("content"),
+                }
+            })
+            .collect()
+    }
+
+    /// The label put in front of the keywords, in the language of the abstract
+    pub(crate) fn keywords_label(&self) -> &'static str {
+        match primary_language(&self.language) {
+            "sv" => "Nyckelord",
+            "nb" | "nn" | "no" => "Nøkkelord",
+            "da" => "Nøgleord",
+            "de" => "Schlüsselwörter",
+            "fr" => "Mots-clés",
+            "es" => "Palabras clave",
+            "fi" => "Avainsanat",
+            "ru" => "Ключевые слова",
+            "el" => "Λέξεις-κλειδιά",
+            "zh" => "关键词",
+            "ar" => "الكلمات المفتاحية",
+            "fa" => "کلیدواژه‌ها",
+            _ => "Keywords",
+        }
+    }
+
+    /// The direction of the text in HTML, which is right to left for Arabic
+    /// and Persian
+    pub(crate) fn direction(&self) -> &'static str {
+        match primary_language(&self.language) {
+            "ar" | "fa" => "rtl",
+            _ => "ltr",
+        }
+    }
+}
+
+fn primary_language(language: &str) -> &str {
+    language.split(['-', '_']).next().unwrap_or_default()
+}
+
+/// The language tags and the babel names of the languages that abstracts may
+/// be written in. Regional variants come before their language, and a tag
+/// comes before its aliases.
+const LANGUAGES: [(&str, &str); 25] = [
+    ("en-US", "american"),
+    ("en-GB", "british"),
+    ("pt-BR", "brazilian"),
+    ("en", "english"),
+    ("sv", "swedish"),
+    ("nb", "norsk"),
+    ("no", "norsk"),
+    ("nn", "nynorsk"),
+    ("da", "danish"),
+    ("fi", "finnish"),
+    ("is", "icelandic"),
+    ("de", "ngerman"),
+    ("nl", "dutch"),
+    ("fr", "french"),
+    ("es", "spanish"),
+    ("it", "italian"),
+    ("pt", "portuguese"),
+    ("pl", "polish"),
+    ("cs", "czech"),
+    ("tr", "turkish"),
+    ("ru", "russian"),
+    ("el", "greek"),
+    ("zh", "chinese"),
+    ("ar", "arabic"),
+    ("fa", "persian"),
+];
+
+/// Get the babel name of a language tag, such as "swedish" for "sv". Returns
+/// `None` for languages that aren't known, which are typeset as English.
+pub(crate) fn babel_language(language: &str) -> Option<&'static str> {
+    let find = |tag: &str| LANGUAGES.iter().find(|(other, _)| *other == tag);
+    find(language)
+        .or_else(|| find(primary_language(language)))
+        .map(|(_, name)| *name)
+}
+
+/// Get the language tag of a babel name, such as "sv" for "swedish"
+pub(crate) fn language_tag(babel_name: &str) -> &'static str {
+    LANGUAGES
+        .iter()
+        .find(|(_, name)| *name == babel_name)
+        .map_or("en", |(tag, _)| *tag)
+}
@@ -0,0 +1,243 @@
+use serde_json::Value;
+
+/// An author of the thesis. Entries in the `authors` list are either just a
+/// name, a name followed by details such as
+/// `Alice Andersson <alice@student.chalmers.se> (Computer Science) 0000-0002-1825-0097`,
+/// or a JSON object with the keys `name`, `email`, `programme`, `orcid` and
+/// `affiliation`.
+#[derive(Clone, Default)]
+pub(crate) struct Author {
+    pub(crate) name: String,
+    pub(crate) email: Option<String>,
+    /// The study programme, for instance "Computer Science and Engineering"
+    pub(crate) programme: Option<String>,
+    pub(crate) orcid: Option<String>,
+    pub(crate) affiliation: Option<String>,
+}
+
+impl Author {
+    pub(crate) fn parse(entry: &str) -> Self {
+        let entry = entry.trim();
+
+        if entry.starts_with('{') {
+            match serde_json::from_str::<Value>(entry) {
+                Ok(json) => return Self::from_json(&json),
+                Err(error) => eprintln!("Invalid author '{entry}': {error}"),
+            }
+        }
+
+        let mut author = Self::default();
+        let mut name = String::new();
+        let mut rest = entry;
+
+        while let Some(c) = rest.chars().next() {
+            let closing = match c {
+                '<' => '>',
+                '(' => ')',
+                _ => {
+                    let end = rest.find(['<', '(']).unwrap_or(rest.len());
+                    name.push_str(&rest[..end]);
+                    rest = &rest[end..];
+                    continue;
+                }
+            };
+
+            let Some(end) = rest.find(closing) else {
+                // not closed, so it is part of the name
+                name.push_str(rest);
+                break;
+            };
+            let value = rest[1..end].trim().to_string();
+            if c == '<' {
+                author.email = Some(value);
+            } else {
+                author.programme = Some(value);
+            }
+            rest = &rest[end + 1..];
+        }
+
+        // an ORCID iD may be written anywhere among the words of the name
+        let mut words = Vec::new();
+        for word in name.split_whitespace() {
+            match parse_orcid(word) {
+                Some(orcid) => author.orcid = Some(orcid),
+                None => words.push(word),
+            }
+        }
+        author.name = words.join(" ");
+
+        author
+    }
+
+    fn from_json(json: &Value) -> Self {
+        let get = |key: &str| {
+            json[key]
+                .as_str()
+                .map(str::trim)
+                .filter(|value| !value.is_empty())
+                .map(str::to_string)
+        };
+
+        let name = get("name").unwrap_or_else(|| {
+            eprintln!("An author is missing a name.");
+            String::new()
+        });
+
+        Self {
+            name,
+            email: get("email"),
+            programme: get("programme"),
+            orcid: get("orcid").map(|orcid| parse_orcid(&orcid).unwrap_or(orcid)),
+            affiliation: get("affiliation"),
+        }
+    }
+
+    /// Whether there is more to show than the name
+    pub(crate) fn has_details(&self) -> bool {
+        self.email.is_some() || self.programme.is_some() || self.orcid.is_some()
+    }
+
+    pub(crate) fn orcid_url(&self) -> Option<String> {
+        self.orcid
+            .as_ref()
+            .map(|orcid| format!("https://orcid.org/{orcid}"))
+    }
+}
+
+/// Get the iD from an ORCID iD such as `0000-0002-1825-0097`, optionally
+/// written as `orcid:...` or as a link to orcid.org
+fn parse_orcid(word: &str) -> Option<String> {
+    let id = word
+        .trim_start_matches("https://")
+        .trim_start_matches("http://")
+        .trim_start_matches("orcid.org/")
+        .trim_start_matches("orcid:");
+
+    let groups: Vec<&str> = id.split('-').collect();
+    let valid = groups.len() == 4
+        && groups.iter().enumerate().all(|(index, group)| {
+            group.len() == 4
+                && group.chars().enumerate().all(|(position, c)| {
+                    // the last character is a check digit, which may be X
+                    c.is_ascii_digit() || (index == 3 && position == 3 && c == 'X')
+                })
+        });
+
+    valid.then(|| id.to_string())
+}
+
+/// Group authors by their affiliation, keeping the order in which each
+/// affiliation first occurs
+pub(crate) fn group_by_affiliation(authors: &[Author]) -> Vec<(Option<&str>, Vec<&Author>)> {
+    let mut groups: Vec<(Option<&str>, Vec<&Author>)> = Vec::new();
+
+    for author in authors {
+        let affiliation = author.affiliation.as_deref();
+        match groups.iter_mut().find(|(a, _)| *a == affiliation) {
+            Some((_, group)) => group.push(author),
+            None => groups.push((affiliation, vec![author])),
+        }
+    }
+
+    groups
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_a_plain_name() {
+        let author = Author::parse("  Alice  Andersson ");
+        assert_eq!(author.name, "Alice Andersson");
+        assert!(!author.has_details());
+    }
+
+    #[test]
+    fn parses_details_in_any_order() {
+        let author = Author::parse(
+            "Alice (Computer Science) 0000-0002-1825-0097 Andersson <alice@student.chalmers.se>",
+        );
+        assert_eq!(author.name, "Alice Andersson");
+        assert_eq!(author.email.as_deref(), Some("alice@student.chalmers.se"));
+        assert_eq!(author.programme.as_deref(), Some("Computer Science"));
+        assert_eq!(author.orcid.as_deref(), Some("0000-0002-1825-0097"));
+        assert_eq!(
+            author.orcid_url().as_deref(),
+            Some("https://orcid.org/0000-0002-1825-0097")
+        );
+    }
+
+    #[test]
+    fn keeps_unclosed_brackets_in_the_name() {
+        let author = Author::parse("Bob <bob@example.com");
+        assert_eq!(author.name, "Bob <bob@example.com");
+        assert_eq!(author.email, None);
+    }
+
+    #[test]
+    fn parses_empty_entries() {
+        let author = Author::parse("");
+        assert_eq!(author.name, "");
+        assert!(!author.has_details());
+    }
+
+    #[test]
+    fn parses_json_entries() {
+        let author = Author::parse(
+            r#"{"name": " Carol ", "email": "", "orcid": "https://orcid.org/0000-0001-5109-3700", "affiliation": "Chalmers"}"#,
+        );
+        assert_eq!(author.name, "Carol");
+        assert_eq!(author.email, None);
+        assert_eq!(author.orcid.as_deref(), Some("0000-0001-5109-3700"));
+        assert_eq!(author.affiliation.as_deref(), Some("Chalmers"));
+
+        assert_eq!(Author::parse(r#"{"email": "x@y.se"}"#).name, "");
+    }
+
+    #[test]
+    fn parses_invalid_json_as_text() {
+        assert_eq!(Author::parse("{Dave").name, "{Dave");
+    }
+
+    #[test]
+    fn parses_orcid_ids() {
+        assert_eq!(
+            parse_orcid("orcid:0000-0002-9079-593X").as_deref(),
+            Some("0000-0002-9079-593X")
+        );
+        assert_eq!(
+            parse_orcid("http://orcid.org/0000-0002-1825-0097").as_deref(),
+            Some("0000-0002-1825-0097")
+        );
+        assert_eq!(parse_orcid("0000-0002-1825-009"), None);
+        assert_eq!(parse_orcid("0000-000X-1825-0097"), None);
+        assert_eq!(parse_orcid("0000-0002-1825-0097-0000"), None);
+        assert_eq!(parse_orcid(""), None);
+    }
+
+    #[test]
+    fn groups_by_affiliation_in_order() {
+        let author = |name: &str, affiliation: Option<&str>| Author {
+            name: name.to_string(),
+            affiliation: affiliation.map(str::to_string),
+            ..Default::default()
+        };
+        let authors = [
+            author("A", Some("X")),
+            author("B", None),
+            author("C", Some("X")),
+        ];
+
+        let groups: Vec<(Option<&str>, Vec<&str>)> = group_by_affiliation(&authors)
+            .into_iter()
+            .map(|(affiliation, authors)| {
+                (
+                    affiliation,
+                    authors.iter().map(|author| author.name.as_str()).collect(),
+                )
+            })
+            .collect();
+        assert_eq!(groups, [(Some("X"), vec!["A", "C"]), (None, vec!["B"])]);
+    }
+}
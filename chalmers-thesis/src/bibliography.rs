@@ -0,0 +1,273 @@
+use crate::{
+    bibtex::{self, BibEntry},
+    citation_style::{CitationStyle, Part},
+    escape::escape_html,
+    Error,
+};
+use serde_json::{json, Value};
+use std::{env, fs};
+
+/// The path to the BibTeX file, given by the constant `sources`
+pub(crate) fn sources_path() -> String {
+    env::var("sources").unwrap_or_else(|_| "sources.bib".to_string())
+}
+
+fn read_sources() -> Result<Vec<BibEntry>, Error> {
+    let path = sources_path();
+    let text = fs::read_to_string(&path)
+        .map_err(|error| Error::Bibliography(format!("Could not read '{path}': {error}")))?;
+    bibtex::parse(&text).map_err(|error| Error::Bibliography(format!("{path}: {error}")))
+}
+
+/// The sources cited by one `[cite]`
+struct Citation {
+    keys: Vec<String>,
+}
+
+fn read_citations() -> Vec<Citation> {
+    let var = env::var("citations").unwrap_or_else(|_| "[]".to_string());
+    let entries: Vec<String> = serde_json::from_str(&var).unwrap();
+
+    entries
+        .iter()
+        .map(|entry| {
+            let entry: Value = serde_json::from_str(entry).unwrap();
+            Citation {
+                keys: entry["keys"]
+                    .as_array()
+                    .unwrap()
+                    .iter()
+                    .filter_map(|key| key.as_str().map(str::to_string))
+                    .collect(),
+            }
+        })
+        .collect()
+}
+
+/// The cited sources, in the order they are first cited, which is how they
+/// are numbered
+fn cited_sources<'a>(citations: &[Citation], sources: &'a [BibEntry]) -> Vec<&'a BibEntry> {
+    let mut cited: Vec<&BibEntry> = Vec::new();
+    for key in citations.iter().flat_map(|citation| &citation.keys) {
+        if let Some(source) = sources.iter().find(|source| &source.key == key) {
+            if !cited.iter().any(|other| other.key == source.key) {
+                cited.push(source);
+            }
+        }
+    }
+
+    cited
+}
+
+fn anchor(key: &str) -> String {
+    let key: String = key
+        .chars()
+        .map(|c| if c.is_alphanumeric() { c } else { '-' })
+        .collect();
+    format!("bib:{key}")
+}
+
+/// Transform `[cite]`, which cites one or more comma separated keys. The
+/// number of a source isn't known until every citation has been pushed to
+/// the list `citations`, so the label is written by `[citation]` afterwards.
+pub(crate) fn transform_cite(input: Value, _to: &str) -> Result<String, Error> {
+    let keys: Vec<&str> = input["data"]
+        .as_str()
+        .unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|key| !key.is_empty())
+        .collect();
+    let note = input["arguments"]["note"].as_str().unwrap_or_default();
+
+    let json = json!([
+        {"name": "list-push", "arguments": {"name": "citations"}, "data": json!({"keys": keys}).to_string()},
+        {"name": "citation", "arguments": {"note": note}, "data": keys.join(",")},
+    ]);
+    Ok(serde_json::to_string(&json).unwrap())
+}
+
+/// Transform `[citation]`, the label of a citation
+pub(crate) fn transform_citation(input: Value, to: &str) -> Result<String, Error> {
+    let keys: Vec<&str> = input["data"]
+        .as_str()
+        .unwrap_or_default()
+        .split(',')
+        .filter(|key| !key.is_empty())
+        .collect();
+    let note = input["arguments"]["note"]
+        .as_str()
+        .unwrap_or_default()
+        .trim();
+
+    let sources = read_sources().unwrap_or_else(|error| {
+        crate::handle_error(error);
+        Vec::new()
+    });
+    for key in &keys {
+        if !sources.iter().any(|source| &source.key == key) {
+            eprintln!("The source '{key}' is not in {}.", sources_path());
+        }
+    }
+
+    let style = CitationStyle::from_env();
+    if to == "latex" {
+        let note = if note.is_empty() {
+            String::new()
+        } else {
+            format!("[{note}]")
+        };
+        let json = json!([format!(
+            "{}{note}{{{}}}",
+            style.cite_command(),
+            keys.join(",")
+        )]);
+        return Ok(serde_json::to_string(&json).unwrap());
+    }
+
+    let mut cited = cited_sources(&read_citations(), &sources);
+    style.sort(&mut cited);
+    let suffixes = style.year_suffixes(&cited);
+
+    let html = to == "html";
+    let labels: Vec<String> = keys
+        .iter()
+        .map(|key| {
+            let position = cited.iter().position(|source| &source.key == key);
+            let label = match position {
+                Some(position) if style.numeric() => (position + 1).to_string(),
+                Some(position) => style.author_year(cited[position], &suffixes[position]),
+                None => format!("{key}??"),
+            };
+
+            match (html, position) {
+                (true, Some(_)) => format!(
+                    r##"<a class="citation" href="#{}">{}</a>"##,
+                    anchor(key),
+                    escape_html(&label)
+                ),
+                (true, None) => format!("<strong>{}</strong>", escape_html(&label)),
+                (false, _) => label,
+            }
+        })
+        .collect();
+
+    let note = match (note.is_empty(), html) {
+        (true, _) => String::new(),
+        (false, true) => format!(", {}", escape_html(note)),
+        (false, false) => format!(", {note}"),
+    };
+    // numbers are bracketed one by one, like [1], [2, p. 4], while authors
+    // are put within one pair of parentheses, like (Knuth, 1984; Lamport, 1994, p. 4)
+    let citation = if style.numeric() {
+        let count = labels.len();
+        labels
+            .iter()
+            .enumerate()
+            .map(|(index, label)| {
+                let note = if index + 1 == count {
+                    note.as_str()
+                } else {
+                    ""
+                };
+                format!("[{label}{note}]")
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    } else {
+        format!("({}{note})", labels.join("; "))
+    };
+
+    let json = json!([citation]);
+    Ok(serde_json::to_string(&json).unwrap())
+}
+
+/// Transform `[bibliography]`, which lists every cited source. Sources that
+/// are never cited are left out, like biblatex does, and reported.
+pub(crate) fn transform_bibliography(input: Value, to: &str) -> Result<String, Error> {
+    let path = sources_path();
+    if let Some(file) = input["arguments"]["file"].as_str() {
+        if !file.is_empty() && file != path {
+            eprintln!(
+                "[bibliography] uses '{path}' and not '{file}'. Set the constant 'sources' to change it."
+            );
+        }
+    }
+
+    // LaTeX still prints the bibliography, since biber reads the file itself
+    let sources = read_sources().unwrap_or_else(|error| {
+        crate::handle_error(error);
+        Vec::new()
+    });
+    let mut cited = cited_sources(&read_citations(), &sources);
+    for source in &sources {
+        if !cited.iter().any(|other| other.key == source.key) {
+            eprintln!("The source '{}' in {path} is never cited.", source.key);
+        }
+    }
+
+    let style = CitationStyle::from_env();
+    style.sort(&mut cited);
+    let suffixes = style.year_suffixes(&cited);
+
+    let mut result = Vec::new();
+    match to {
+        "latex" => result.push(json!("\\printbibliography[heading=bibintoc]\n")),
+        "html" if style.numeric() => {
+            result.push(json!(r#"<dl class="bibliography">"#));
+            for (number, source) in cited.iter().enumerate() {
+                result.push(json!(format!(
+                    r#"<dt>[{}]</dt><dd id="{}">"#,
+                    number + 1,
+                    anchor(&source.key)
+                )));
+                result.append(&mut render(&style.format(source, ""), to));
+                result.push(json!("</dd>"));
+            }
+            result.push(json!("</dl>"));
+        }
+        "html" => {
+            result.push(json!(r#"<ul class="bibliography">"#));
+            for (source, suffix) in cited.iter().zip(&suffixes) {
+                result.push(json!(format!(r#"<li id="{}">"#, anchor(&source.key))));
+                result.append(&mut render(&style.format(source, suffix), to));
+                result.push(json!("</li>"));
+            }
+            result.push(json!("</ul>"));
+        }
+        _ => {
+            for (number, (source, suffix)) in cited.iter().zip(&suffixes).enumerate() {
+                if style.numeric() {
+                    result.push(json!(format!("[{}] ", number + 1)));
+                }
+                result.append(&mut render(&style.format(source, suffix), to));
+                result.push(json!("\n\n"));
+            }
+        }
+    }
+
+    Ok(serde_json::to_string(&result).unwrap())
+}
+
+fn render(parts: &[Part], to: &str) -> Vec<Value> {
+    let html = to == "html";
+    parts
+        .iter()
+        .flat_map(|part| match part {
+            Part::Text(text) if html => vec![json!(escape_html(text))],
+            Part::Text(text) => vec![json!(text)],
+            Part::Title(title) => vec![json!({"name": "inline_content", "data": title})],
+            Part::Italic(text) if html => vec![
+                json!("<i>"),
+                json!({"name": "inline_content", "data": text}),
+                json!("</i>"),
+            ],
+            Part::Italic(text) => vec![json!({"name": "inline_content", "data": text})],
+            Part::Url(url) if html => {
+                let url = escape_html(url);
+                vec![json!(format!(r#"<a href="{url}">{url}</a>"#))]
+            }
+            Part::Url(url) => vec![json!(url)],
+        })
+        .collect()
+}
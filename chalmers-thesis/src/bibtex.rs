@@ -0,0 +1,693 @@
+use std::collections::HashMap;
+
+/// An entry in a BibTeX file, such as `@article{key, title = {...}}`
+pub(crate) struct BibEntry {
+    /// The entry type in lowercase, e.g. "article"
+    pub(crate) kind: String,
+    pub(crate) key: String,
+    /// The fields with lowercase names and their values as written, but with
+    /// strings and `#` concatenations resolved
+    fields: Vec<(String, String)>,
+}
+
+/// A person in a name list such as `author`. Names written within braces,
+/// like `{Eclipse Foundation}`, are kept whole as the last name.
+pub(crate) struct Name {
+    pub(crate) first: String,
+    pub(crate) last: String,
+}
+
+impl Name {
+    fn parse(raw: &str) -> Self {
+        let raw = raw.trim();
+        if is_braced(raw) {
+            return Self {
+                first: String::new(),
+                last: clean(raw),
+            };
+        }
+
+        // "Last, First" or "Last, Jr, First"
+        let parts = split_top_level(raw, |rest| rest.starts_with(',').then_some(1));
+        if parts.len() > 1 {
+            return Self {
+                first: clean(parts.last().unwrap()),
+                last: clean(parts[0]),
+            };
+        }
+
+        // "First Last"
+        let words = split_top_level(raw, |rest| {
+            rest.chars()
+                .next()
+                .filter(|c| c.is_whitespace())
+                .map(char::len_utf8)
+        });
+        let words: Vec<&str> = words.into_iter().filter(|word| !word.is_empty()).collect();
+        match words.split_last() {
+            Some((last, first)) => Self {
+                first: clean(&first.join(" ")),
+                last: clean(last),
+            },
+            None => Self {
+                first: String::new(),
+                last: String::new(),
+            },
+        }
+    }
+
+    /// The initials of the first names, such as "M. D." for "Malcolm Douglas"
+    pub(crate) fn initials(&self) -> String {
+        self.first
+            .split_whitespace()
+            .map(|name| {
+                name.split('-')
+                    .filter_map(|part| part.chars().next())
+                    .map(|c| format!("{c}."))
+                    .collect::<Vec<_>>()
+                    .join("-")
+            })
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// Whether this is "others", as in "A and B and others"
+    pub(crate) fn is_others(&self) -> bool {
+        self.first.is_empty() && self.last == "others"
+    }
+}
+
+impl BibEntry {
+    /// Get a field with the markup removed
+    pub(crate) fn get(&self, name: &str) -> Option<String> {
+        self.raw(name).map(clean).filter(|value| !value.is_empty())
+    }
+
+    /// Get a field that is written as it is, such as `url` or `doi`
+    pub(crate) fn verbatim(&self, name: &str) -> Option<String> {
+        self.raw(name)
+            .map(|value| value.trim().trim_start_matches('{').trim_end_matches('}'))
+            .filter(|value| !value.is_empty())
+            .map(str::to_string)
+    }
+
+    fn raw(&self, name: &str) -> Option<&str> {
+        self.fields
+            .iter()
+            .find(|(field, _)| field == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Get the people of a name list such as `author` or `editor`
+    pub(crate) fn names(&self, name: &str) -> Vec<Name> {
+        let Some(raw) = self.raw(name) else {
+            return Vec::new();
+        };
+
+        split_top_level(raw, and_separator)
+            .into_iter()
+            .map(Name::parse)
+            .filter(|name| !name.last.is_empty())
+            .collect()
+    }
+
+    /// The year of the entry, taken from `year` or `date`
+    pub(crate) fn year(&self) -> Option<String> {
+        self.get("year")
+            .or_else(|| self.get("date").map(|date| date.chars().take(4).collect()))
+    }
+}
+
+/// Parse the entries of a BibTeX file. Text outside of entries is ignored,
+/// like BibTeX does, so comments can be written freely between them.
+pub(crate) fn parse(text: &str) -> Result<Vec<BibEntry>, String> {
+    let mut parser = Parser {
+        chars: text.trim_start_matches('\u{feff}').chars().collect(),
+        position: 0,
+        strings: HashMap::new(),
+    };
+
+    let mut entries: Vec<BibEntry> = Vec::new();
+    while let Some(entry) = parser.next_entry()? {
+        if entries.iter().any(|other| other.key == entry.key) {
+            eprintln!("The source '{}' is declared twice.", entry.key);
+            continue;
+        }
+        entries.push(entry);
+    }
+
+    Ok(entries)
+}
+
+struct Parser {
+    chars: Vec<char>,
+    position: usize,
+    /// Abbreviations declared using `@string`
+    strings: HashMap<String, String>,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.position).copied()
+    }
+
+    fn line(&self) -> usize {
+        self.chars[..self.position.min(self.chars.len())]
+            .iter()
+            .filter(|c| **c == '\n')
+            .count()
+            + 1
+    }
+
+    fn error(&self, message: &str) -> String {
+        format!("Invalid BibTeX on line {}: {message}", self.line())
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.position += 1;
+        }
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), String> {
+        self.skip_whitespace();
+        if self.peek() == Some(expected) {
+            self.position += 1;
+            Ok(())
+        } else {
+            Err(self.error(&format!("expected '{expected}'")))
+        }
+    }
+
+    fn identifier(&mut self) -> String {
+        self.skip_whitespace();
+        let start = self.position;
+        while self
+            .peek()
+            .is_some_and(|c| c.is_alphanumeric() || "-_:.+/'".contains(c))
+        {
+            self.position += 1;
+        }
+        self.chars[start..self.position].iter().collect()
+    }
+
+    /// Read up to the brace matching one that was just read
+    fn braced(&mut self) -> Result<String, String> {
+        let start = self.position;
+        let mut depth = 1;
+        while let Some(c) = self.peek() {
+            self.position += 1;
+            match c {
+                '\\' => self.position += 1,
+                '{' => depth += 1,
+                '}' => {
+                    depth -= 1;
+                    if depth == 0 {
+                        return Ok(self.chars[start..self.position - 1].iter().collect());
+                    }
+                }
+                _ => {}
+            }
+        }
+        Err(self.error("missing '}'"))
+    }
+
+    fn quoted(&mut self) -> Result<String, String> {
+        let start = self.position;
+        let mut depth = 0;
+        while let Some(c) = self.peek() {
+            self.position += 1;
+            match c {
+                '\\' => self.position += 1,
+                '{' => depth += 1,
+                '}' => depth -= 1,
+                '"' if depth == 0 => {
+                    return Ok(self.chars[start..self.position - 1].iter().collect())
+                }
+                _ => {}
+            }
+        }
+        Err(self.error("missing '\"'"))
+    }
+
+    /// Read a value, which may be several parts joined using `#`
+    fn value(&mut self) -> Result<String, String> {
+        let mut value = String::new();
+        loop {
+            self.skip_whitespace();
+            match self.peek() {
+                Some('{') => {
+                    self.position += 1;
+                    value.push_str(&self.braced()?);
+                }
+                Some('"') => {
+                    self.position += 1;
+                    value.push_str(&self.quoted()?);
+                }
+                Some(c) if c.is_alphanumeric() => {
+                    let name = self.identifier();
+                    match self.strings.get(&name.to_lowercase()) {
+                        Some(string) => value.push_str(string),
+                        None => match month_number(&name) {
+                            Some(month) => value.push_str(month),
+                            None if name.chars().all(|c| c.is_ascii_digit()) => {
+                                value.push_str(&name)
+                            }
+                            None => {
+                                eprintln!(
+                                    "Unknown BibTeX string '{name}' on line {}.",
+                                    self.line()
+                                );
+                                value.push_str(&name);
+                            }
+                        },
+                    }
+                }
+                _ => return Err(self.error("expected a value")),
+            }
+
+            self.skip_whitespace();
+            if self.peek() == Some('#') {
+                self.position += 1;
+            } else {
+                return Ok(value);
+            }
+        }
+    }
+
+    fn next_entry(&mut self) -> Result<Option<BibEntry>, String> {
+        loop {
+            // skip to the next entry
+            while self.peek().is_some_and(|c| c != '@') {
+                self.position += 1;
+            }
+            if self.peek().is_none() {
+                return Ok(None);
+            }
+            self.position += 1;
+
+            let kind = self.identifier().to_lowercase();
+            self.skip_whitespace();
+            let closing = match self.peek() {
+                Some('{') => '}',
+                Some('(') => ')',
+                _ => return Err(self.error(&format!("expected '{{' after '@{kind}'"))),
+            };
+            self.position += 1;
+
+            match kind.as_str() {
+                "comment" | "preamble" => {
+                    self.braced()?;
+                    continue;
+                }
+                "string" => {
+                    let name = self.identifier().to_lowercase();
+                    self.expect('=')?;
+                    let value = self.value()?;
+                    self.strings.insert(name, value);
+                    self.expect(closing)?;
+                    continue;
+                }
+                _ => {}
+            }
+
+            let key = self.identifier();
+            if key.is_empty() {
+                return Err(self.error(&format!("missing the key of an '@{kind}' entry")));
+            }
+
+            let mut fields = Vec::new();
+            loop {
+                self.skip_whitespace();
+                match self.peek() {
+                    Some(',') => self.position += 1,
+                    Some(c) if c == closing => {
+                        self.position += 1;
+                        break;
+                    }
+                    _ => return Err(self.error(&format!("expected ',' in '{key}'"))),
+                }
+
+                self.skip_whitespace();
+                if self.peek() == Some(closing) {
+                    self.position += 1;
+                    break;
+                }
+
+                let name = self.identifier().to_lowercase();
+                if name.is_empty() {
+                    return Err(self.error(&format!("expected a field name in '{key}'")));
+                }
+                self.expect('=')?;
+                fields.push((name, self.value()?));
+            }
+
+            return Ok(Some(BibEntry { kind, key, fields }));
+        }
+    }
+}
+
+fn month_number(name: &str) -> Option<&'static str> {
+    let number = match name.to_lowercase().as_str() {
+        "jan" => "1",
+        "feb" => "2",
+        "mar" => "3",
+        "apr" => "4",
+        "may" => "5",
+        "jun" => "6",
+        "jul" => "7",
+        "aug" => "8",
+        "sep" => "9",
+        "oct" => "10",
+        "nov" => "11",
+        "dec" => "12",
+        _ => return None,
+    };
+
+    Some(number)
+}
+
+/// Whether all of the text is within one pair of braces
+fn is_braced(text: &str) -> bool {
+    if !text.starts_with('{') {
+        return false;
+    }
+
+    let mut depth = 0;
+    for (index, c) in text.char_indices() {
+        match c {
+            '{' => depth += 1,
+            '}' => {
+                depth -= 1;
+                if depth == 0 {
+                    return index == text.len() - 1;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    false
+}
+
+/// The length of the " and " separating names, if the text starts with one
+fn and_separator(text: &str) -> Option<usize> {
+    let rest = text.trim_start();
+    let space = text.len() - rest.len();
+    let is_and = space > 0
+        && rest.len() > 3
+        && rest[..3].eq_ignore_ascii_case("and")
+        && rest[3..].starts_with(char::is_whitespace);
+
+    is_and.then_some(space + 3)
+}
+
+/// Split text at separators that aren't within braces, where `separator`
+/// gives the length of a separator that the rest of the text starts with
+fn split_top_level(text: &str, separator: impl Fn(&str) -> Option<usize>) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut depth = 0;
+    let mut start = 0;
+
+    for (index, c) in text.char_indices() {
+        if index < start {
+            continue;
+        }
+        match c {
+            '{' => depth += 1,
+            '}' => depth -= 1,
+            _ if depth == 0 => {
+                if let Some(length) = separator(&text[index..]) {
+                    parts.push(text[start..index].trim());
+                    start = index + length;
+                }
+            }
+            _ => {}
+        }
+    }
+    parts.push(text[start..].trim());
+
+    parts
+}
+
+/// Remove the LaTeX markup of a value, turning accents and escaped characters
+/// into their Unicode equivalents
+pub(crate) fn clean(text: &str) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let mut result = String::new();
+    let mut index = 0;
+
+    while index < chars.len() {
+        let c = chars[index];
+        index += 1;
+        match c {
+            '{' | '}' => {}
+            '~' => result.push('\u{a0}'),
+            '-' if chars.get(index) == Some(&'-') => {
+                if chars.get(index + 1) == Some(&'-') {
+                    result.push('—');
+                    index += 2;
+                } else {
+                    result.push('–');
+                    index += 1;
+                }
+            }
+            '\\' => {
+                let Some(&next) = chars.get(index) else {
+                    break;
+                };
+                index += 1;
+
+                // accents named by a letter, such as \c, are followed by a non-letter
+                let accent = combining_accent(next).filter(|_| {
+                    !next.is_alphabetic() || !chars.get(index).is_some_and(|c| c.is_alphabetic())
+                });
+                if let Some(mark) = accent {
+                    // the accented letter, as in \"o, \"{o} or \c{c}
+                    while chars.get(index).is_some_and(|c| *c == '{' || *c == ' ') {
+                        index += 1;
+                    }
+                    if let Some(&letter) = chars.get(index) {
+                        index += 1;
+                        let letter = if letter == '\\' && chars.get(index) == Some(&'i') {
+                            index += 1;
+                            'i'
+                        } else {
+                            letter
+                        };
+                        result.push(letter);
+                        result.push(mark);
+                    }
+                    continue;
+                }
+
+                if !next.is_alphabetic() {
+                    // escaped characters such as \& and \%
+                    result.push(next);
+                    continue;
+                }
+
+                let start = index - 1;
+                while chars.get(index).is_some_and(|c| c.is_alphabetic()) {
+                    index += 1;
+                }
+                let command: String = chars[start..index].iter().collect();
+                if let Some(letter) = letter_command(&command) {
+                    result.push_str(letter);
+                    // the space ending a command isn't part of the text
+                    if chars.get(index) == Some(&' ') {
+                        index += 1;
+                    }
+                }
+                // other commands, such as \emph, are removed but their
+                // argument is kept
+            }
+            c if c.is_whitespace() => {
+                if !result.ends_with(' ') {
+                    result.push(' ');
+                }
+            }
+            c => result.push(c),
+        }
+    }
+
+    result.trim().to_string()
+}
+
+fn combining_accent(command: char) -> Option<char> {
+    let mark = match command {
+        '"' => '\u{308}',
+        '\'' => '\u{301}',
+        '`' => '\u{300}',
+        '^' => '\u{302}',
+        '~' => '\u{303}',
+        '=' => '\u{304}',
+        '.' => '\u{307}',
+        'c' => '\u{327}',
+        'u' => '\u{306}',
+        'v' => '\u{30c}',
+        'H' => '\u{30b}',
+        'r' => '\u{30a}',
+        _ => return None,
+    };
+
+    Some(mark)
+}
+
+fn letter_command(command: &str) -> Option<&'static str> {
+    let letter = match command {
+        "aa" => "å",
+        "AA" => "Å",
+        "ae" => "æ",
+        "AE" => "Æ",
+        "o" => "ø",
+        "O" => "Ø",
+        "oe" => "œ",
+        "OE" => "Œ",
+        "ss" => "ß",
+        "l" => "ł",
+        "L" => "Ł",
+        "i" => "ı",
+        _ => return None,
+    };
+
+    Some(letter)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_one(text: &str) -> BibEntry {
+        let mut entries = parse(text).unwrap();
+        assert_eq!(entries.len(), 1);
+        entries.remove(0)
+    }
+
+    #[test]
+    fn parses_braced_and_quoted_values() {
+        let entry = parse_one(
+            r#"@Article{knuth84,
+                Title = {Literate {P}rogramming},
+                journal = "The {"}Computer{"} Journal",
+                year = 1984,
+                month = may,
+            }"#,
+        );
+        assert_eq!(entry.kind, "article");
+        assert_eq!(entry.key, "knuth84");
+        assert_eq!(entry.get("title").as_deref(), Some("Literate Programming"));
+        assert_eq!(
+            entry.get("journal").as_deref(),
+            Some("The \"Computer\" Journal")
+        );
+        assert_eq!(entry.year().as_deref(), Some("1984"));
+        assert_eq!(entry.get("month").as_deref(), Some("5"));
+        assert_eq!(entry.get("note"), None);
+    }
+
+    #[test]
+    fn resolves_strings_and_concatenations() {
+        let entry = parse_one(
+            r#"@string{acm = "ACM"}
+            @comment{ignored @book{nope, title = {x}} }
+            Text between entries is ignored.
+            @misc(site, publisher = acm # { Press}, date = {2023-05-01})"#,
+        );
+        assert_eq!(entry.key, "site");
+        assert_eq!(entry.get("publisher").as_deref(), Some("ACM Press"));
+        assert_eq!(entry.year().as_deref(), Some("2023"));
+    }
+
+    #[test]
+    fn parses_empty_input() {
+        assert!(parse("").unwrap().is_empty());
+        assert!(parse("\u{feff}  % just a comment\n").unwrap().is_empty());
+    }
+
+    #[test]
+    fn skips_duplicate_keys() {
+        let entries = parse("@misc{a, title = {1}} @misc{a, title = {2}}").unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].get("title").as_deref(), Some("1"));
+    }
+
+    #[test]
+    fn rejects_malformed_entries() {
+        let error = |text: &str| parse(text).err().unwrap();
+
+        assert_eq!(
+            error("@misc{a,\n title = {open"),
+            "Invalid BibTeX on line 2: missing '}'"
+        );
+        assert_eq!(
+            error("@misc{a, title = \"open}"),
+            "Invalid BibTeX on line 1: missing '\"'"
+        );
+        assert_eq!(
+            error("@misc{, title = {x}}"),
+            "Invalid BibTeX on line 1: missing the key of an '@misc' entry"
+        );
+        assert_eq!(
+            error("@misc{a title = {x}}"),
+            "Invalid BibTeX on line 1: expected ',' in 'a'"
+        );
+        assert_eq!(
+            error("@misc{a, title {x}}"),
+            "Invalid BibTeX on line 1: expected '='"
+        );
+        assert_eq!(
+            error("@misc{a, title = }"),
+            "Invalid BibTeX on line 1: expected a value"
+        );
+        assert_eq!(
+            error("@misc a"),
+            "Invalid BibTeX on line 1: expected '{' after '@misc'"
+        );
+    }
+
+    #[test]
+    fn parses_names() {
+        let entry = parse_one(
+            r#"@book{b, author = {Knuth, Donald E. and Malcolm-Douglas {van Dam} and {Eclipse Foundation} and others}}"#,
+        );
+        let names = entry.names("author");
+        let names: Vec<(&str, &str)> = names
+            .iter()
+            .map(|name| (name.first.as_str(), name.last.as_str()))
+            .collect();
+        assert_eq!(
+            names,
+            [
+                ("Donald E.", "Knuth"),
+                ("Malcolm-Douglas", "van Dam"),
+                ("", "Eclipse Foundation"),
+                ("", "others"),
+            ]
+        );
+
+        let names = entry.names("author");
+        assert_eq!(names[0].initials(), "D. E.");
+        assert_eq!(names[1].initials(), "M.-D.");
+        assert!(names[3].is_others());
+        assert!(entry.names("editor").is_empty());
+    }
+
+    #[test]
+    fn cleans_markup() {
+        assert_eq!(clean(r#"K{\"o}nig \& Sons"#), "Ko\u{308}nig & Sons");
+        assert_eq!(clean(r"Fran\c{c}ois"), "Franc\u{327}ois");
+        assert_eq!(clean(r"\emph{pages} 1--2,   3---4"), "pages 1–2, 3—4");
+        assert_eq!(clean("a~b"), "a\u{a0}b");
+        assert_eq!(clean("trailing \\"), "trailing");
+    }
+
+    #[test]
+    fn keeps_verbatim_fields() {
+        let entry = parse_one(r"@online{w, url = {https://example.com/a_b%20c}}");
+        assert_eq!(
+            entry.verbatim("url").as_deref(),
+            Some("https://example.com/a_b%20c")
+        );
+    }
+}
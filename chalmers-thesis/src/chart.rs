@@ -0,0 +1,504 @@
+use crate::{csv::CsvTable, escape::escape_html, svg::svg_to_pgf, Error};
+use serde_json::{json, Value};
+use std::fmt::Write;
+
+/// Colours of the series, starting with the primary colour of the HTML template
+pub(crate) const PALETTE: [&str; 6] = [
+    "#0f8b8d", "#ec9a29", "#a8201a", "#143642", "#7b9e89", "#6d597a",
+];
+
+pub(crate) const FONT: &str = "Lora, serif";
+
+/// Space around the plot area for axes, tick labels and axis labels
+const MARGIN_LEFT: f64 = 64.0;
+const MARGIN_RIGHT: f64 = 16.0;
+const MARGIN_TOP: f64 = 16.0;
+const MARGIN_BOTTOM: f64 = 56.0;
+const LEGEND_WIDTH: f64 = 130.0;
+
+#[derive(PartialEq)]
+enum ChartType {
+    Bar,
+    GroupedBar,
+    Line,
+    Scatter,
+}
+
+/// A named series of values, one for each row of the data
+struct Series {
+    name: String,
+    values: Vec<Option<f64>>,
+}
+
+struct Chart {
+    chart_type: ChartType,
+    /// The first column of the data, categories or x values
+    categories: Vec<String>,
+    series: Vec<Series>,
+    x_label: String,
+    y_label: String,
+    legend: bool,
+    width: f64,
+    height: f64,
+}
+
+/// Transform `[chart]`, which draws a chart from CSV data as an SVG image.
+/// The first column holds the categories (or x values) and every following
+/// column is a series.
+pub(crate) fn transform_chart(input: Value, to: &str) -> Result<String, Error> {
+    let data = input["data"].as_str().unwrap_or_default();
+    let arguments = &input["arguments"];
+
+    let Some(table) = CsvTable::from_arguments(data, arguments)? else {
+        return Err(Error::Csv("The chart has no data".to_string()));
+    };
+
+    let chart = Chart::new(table, arguments)?;
+    let svg = chart.to_svg();
+
+    let json = match to {
+        "html" => json!([svg]),
+        "latex" => {
+            let picture = svg_to_pgf(&svg).map_err(|error| {
+                Error::Svg(format!("The chart could not be drawn using PGF: {error}"))
+            })?;
+            json!([format!("\\resizebox{{\\linewidth}}{{!}}{{{picture}}}\n")])
+        }
+        other => {
+            eprintln!("Cannot convert chart to {other}");
+            json!([])
+        }
+    };
+
+    Ok(serde_json::to_string(&json).unwrap())
+}
+
+impl Chart {
+    fn new(table: CsvTable, arguments: &Value) -> Result<Self, Error> {
+        let chart_type = match arguments["type"].as_str().unwrap_or("bar") {
+            "grouped-bar" => ChartType::GroupedBar,
+            "line" => ChartType::Line,
+            "scatter" => ChartType::Scatter,
+            _ => ChartType::Bar,
+        };
+
+        let mut rows = table.rows.into_iter();
+        let header = rows.next().unwrap_or_default();
+        let rows: Vec<Vec<String>> = rows.collect();
+
+        if header.len() < 2 {
+            return Err(Error::Csv(
+                "A chart needs at least one column of categories and one of values".to_string(),
+            ));
+        }
+
+        if rows.is_empty() {
+            return Err(Error::Csv("The chart has no rows".to_string()));
+        }
+
+        let categories: Vec<String> = rows
+            .iter()
+            .map(|row| row.first().cloned().unwrap_or_default().trim().to_string())
+            .collect();
+
+        // empty and non-numeric cells are left out, but a value that is
+        // infinite or not a number can't be drawn
+        let mut series = Vec::new();
+        for (index, name) in header.iter().enumerate().skip(1) {
+            let name = name.trim().to_string();
+            let mut values = Vec::new();
+            for row in &rows {
+                let cell = row.get(index).map(|cell| cell.trim()).unwrap_or_default();
+                let value = cell.parse::<f64>().ok();
+                if value.is_some_and(|value| !value.is_finite()) {
+                    return Err(Error::Csv(format!(
+                        "The value '{cell}' of the series '{name}' is not a finite number"
+                    )));
+                }
+                values.push(value);
+            }
+            series.push(Series { name, values });
+        }
+
+        if chart_type == ChartType::Bar && series.len() > 1 {
+            return Err(Error::Csv(format!(
+                "A bar chart shows one series, but there are {}. Use type=grouped-bar to show all of them, or choose one using columns.",
+                series.len()
+            )));
+        }
+        if chart_type == ChartType::Scatter {
+            if let Some(category) = categories
+                .iter()
+                .find(|category| x_value(category).is_none())
+            {
+                return Err(Error::Csv(format!(
+                    "The x value '{category}' of a scatter plot is not a finite number"
+                )));
+            }
+        }
+
+        let x_label = arguments["x-label"].as_str().unwrap_or_default();
+        let y_label = arguments["y-label"].as_str().unwrap_or_default();
+
+        Ok(Self {
+            chart_type,
+            categories,
+            series,
+            x_label: x_label.to_string(),
+            y_label: y_label.to_string(),
+            legend: arguments["legend"].as_str() != Some("false"),
+            width: arguments["width"].as_f64().unwrap_or(600.0),
+            height: arguments["height"].as_f64().unwrap_or(360.0),
+        })
+    }
+
+    /// Whether the x axis is numeric, as for scatter plots and line charts
+    /// with numbers in the first column
+    fn numeric_x(&self) -> Option<Vec<f64>> {
+        let plots_points = matches!(self.chart_type, ChartType::Scatter | ChartType::Line);
+        if !plots_points || self.categories.is_empty() {
+            return None;
+        }
+
+        self.categories
+            .iter()
+            .map(|category| x_value(category))
+            .collect()
+    }
+
+    fn show_legend(&self) -> bool {
+        self.legend && (self.series.len() > 1 || self.chart_type != ChartType::Bar)
+    }
+
+    fn to_svg(&self) -> String {
+        let legend_width = if self.show_legend() {
+            LEGEND_WIDTH
+        } else {
+            0.0
+        };
+        let plot_left = MARGIN_LEFT;
+        let plot_right = self.width - MARGIN_RIGHT - legend_width;
+        let plot_top = MARGIN_TOP;
+        let plot_bottom = self.height - MARGIN_BOTTOM;
+
+        let values = self.series.iter().flat_map(|s| s.values.iter().flatten());
+        let (min, max) = values.fold((0.0_f64, 0.0_f64), |(min, max), &v| {
+            (min.min(v), max.max(v))
+        });
+        let y_ticks = nice_ticks(min, max);
+        let (y_min, y_max) = (y_ticks[0], *y_ticks.last().unwrap());
+        let y =
+            |value: f64| plot_bottom - (value - y_min) / (y_max - y_min) * (plot_bottom - plot_top);
+
+        let mut svg = String::new();
+        write!(
+            svg,
+            r#"<svg class="chart" xmlns="http://www.w3.org/2000/svg" viewBox="0 0 {w} {h}" width="{w}" height="{h}" font-family="{FONT}" font-size="12" role="img">"#,
+            w = self.width,
+            h = self.height
+        )
+        .unwrap();
+        svg.push_str(r##"<rect width="100%" height="100%" fill="#ffffff"/>"##);
+
+        // horizontal grid lines and y axis ticks
+        for tick in &y_ticks {
+            let ty = y(*tick);
+            write!(
+                svg,
+                r##"<line x1="{plot_left}" y1="{ty:.1}" x2="{plot_right}" y2="{ty:.1}" stroke="#e4e4e4"/><text x="{:.1}" y="{:.1}" text-anchor="end">{}</text>"##,
+                plot_left - 6.0,
+                ty + 4.0,
+                format_number(*tick)
+            )
+            .unwrap();
+        }
+
+        // the data itself, and ticks on the x axis
+        let plot_width = plot_right - plot_left;
+        match self.numeric_x() {
+            Some(xs) => {
+                let x_ticks = nice_ticks(
+                    xs.iter().copied().fold(f64::INFINITY, f64::min),
+                    xs.iter().copied().fold(f64::NEG_INFINITY, f64::max),
+                );
+                let (x_min, x_max) = (x_ticks[0], *x_ticks.last().unwrap());
+                let x = |value: f64| plot_left + (value - x_min) / (x_max - x_min) * plot_width;
+
+                for tick in &x_ticks {
+                    write!(
+                        svg,
+                        r#"<text x="{:.1}" y="{:.1}" text-anchor="middle">{}</text>"#,
+                        x(*tick),
+                        plot_bottom + 18.0,
+                        format_number(*tick)
+                    )
+                    .unwrap();
+                }
+
+                for (index, series) in self.series.iter().enumerate() {
+                    let colour = PALETTE[index % PALETTE.len()];
+                    let points: Vec<(f64, f64)> = xs
+                        .iter()
+                        .zip(&series.values)
+                        .filter_map(|(&px, py)| py.map(|py| (x(px), y(py))))
+                        .collect();
+                    self.draw_points(&mut svg, &points, colour);
+                }
+            }
+            None => {
+                let count = self.categories.len().max(1) as f64;
+                let band = plot_width / count;
+
+                for (index, category) in self.categories.iter().enumerate() {
+                    write!(
+                        svg,
+                        r#"<text x="{:.1}" y="{:.1}" text-anchor="middle">{}</text>"#,
+                        plot_left + band * (index as f64 + 0.5),
+                        plot_bottom + 18.0,
+                        escape_html(category)
+                    )
+                    .unwrap();
+                }
+
+                if self.chart_type == ChartType::Line {
+                    for (index, series) in self.series.iter().enumerate() {
+                        let colour = PALETTE[index % PALETTE.len()];
+                        let points: Vec<(f64, f64)> = series
+                            .values
+                            .iter()
+                            .enumerate()
+                            .filter_map(|(i, v)| {
+                                v.map(|v| (plot_left + band * (i as f64 + 0.5), y(v)))
+                            })
+                            .collect();
+                        self.draw_points(&mut svg, &points, colour);
+                    }
+                } else {
+                    // bars of the same category are placed next to each other
+                    let bar_width = band * 0.8 / self.series.len() as f64;
+                    for (index, series) in self.series.iter().enumerate() {
+                        let colour = PALETTE[index % PALETTE.len()];
+                        for (i, value) in series.values.iter().enumerate() {
+                            let Some(value) = value else { continue };
+                            let bx = plot_left + band * (i as f64 + 0.1) + bar_width * index as f64;
+                            let (top, bottom) = (y(value.max(0.0)), y(value.min(0.0)));
+                            write!(
+                                svg,
+                                r#"<rect x="{bx:.1}" y="{top:.1}" width="{bar_width:.1}" height="{:.1}" fill="{colour}"><title>{}: {}</title></rect>"#,
+                                bottom - top,
+                                escape_html(&series.name),
+                                format_number(*value)
+                            )
+                            .unwrap();
+                        }
+                    }
+                }
+            }
+        }
+
+        // axes
+        write!(
+            svg,
+            r##"<line x1="{plot_left}" y1="{plot_top}" x2="{plot_left}" y2="{plot_bottom}" stroke="#252525"/><line x1="{plot_left}" y1="{:.1}" x2="{plot_right}" y2="{:.1}" stroke="#252525"/>"##,
+            y(0.0_f64.clamp(y_min, y_max)),
+            y(0.0_f64.clamp(y_min, y_max)),
+        )
+        .unwrap();
+
+        // axis labels
+        if !self.x_label.is_empty() {
+            write!(
+                svg,
+                r#"<text x="{:.1}" y="{:.1}" text-anchor="middle" font-size="14">{}</text>"#,
+                plot_left + plot_width / 2.0,
+                self.height - 12.0,
+                escape_html(&self.x_label)
+            )
+            .unwrap();
+        }
+        if !self.y_label.is_empty() {
+            let cy = plot_top + (plot_bottom - plot_top) / 2.0;
+            write!(
+                svg,
+                r#"<text x="16" y="{cy:.1}" text-anchor="middle" font-size="14" transform="rotate(-90 16 {cy:.1})">{}</text>"#,
+                escape_html(&self.y_label)
+            )
+            .unwrap();
+        }
+
+        if self.show_legend() {
+            for (index, series) in self.series.iter().enumerate() {
+                let ly = plot_top + 20.0 * index as f64;
+                write!(
+                    svg,
+                    r#"<rect x="{:.1}" y="{ly:.1}" width="12" height="12" fill="{}"/><text x="{:.1}" y="{:.1}">{}</text>"#,
+                    plot_right + 16.0,
+                    PALETTE[index % PALETTE.len()],
+                    plot_right + 34.0,
+                    ly + 10.0,
+                    escape_html(&series.name)
+                )
+                .unwrap();
+            }
+        }
+
+        svg.push_str("</svg>");
+        svg
+    }
+
+    /// Draw a series as a line with markers, or only markers for scatter plots
+    fn draw_points(&self, svg: &mut String, points: &[(f64, f64)], colour: &str) {
+        if self.chart_type == ChartType::Line && points.len() > 1 {
+            let path = points
+                .iter()
+                .map(|(x, y)| format!("{x:.1},{y:.1}"))
+                .collect::<Vec<_>>()
+                .join(" ");
+            write!(
+                svg,
+                r#"<polyline points="{path}" fill="none" stroke="{colour}" stroke-width="2"/>"#
+            )
+            .unwrap();
+        }
+
+        for (x, y) in points {
+            write!(
+                svg,
+                r#"<circle cx="{x:.1}" cy="{y:.1}" r="3.5" fill="{colour}"/>"#
+            )
+            .unwrap();
+        }
+    }
+}
+
+/// Parse a value of a numeric x axis
+fn x_value(category: &str) -> Option<f64> {
+    category
+        .parse()
+        .ok()
+        .filter(|value: &f64| value.is_finite())
+}
+
+/// Get evenly spaced ticks at "nice" numbers (1, 2 or 5 times a power of
+/// ten) covering the range from min to max
+fn nice_ticks(min: f64, max: f64) -> Vec<f64> {
+    let (min, max) = if (max - min).abs() < f64::EPSILON {
+        (min - 1.0, max + 1.0)
+    } else {
+        (min, max)
+    };
+
+    let rough_step = (max - min) / 5.0;
+    let magnitude = 10_f64.powf(rough_step.log10().floor());
+    let step = [1.0, 2.0, 5.0, 10.0]
+        .into_iter()
+        .map(|factor| factor * magnitude)
+        .find(|&step| step >= rough_step)
+        .unwrap();
+
+    let first = (min / step).floor() as i64;
+    let last = (max / step).ceil() as i64;
+    (first..=last).map(|i| i as f64 * step).collect()
+}
+
+/// Format a number without trailing zeroes
+fn format_number(number: f64) -> String {
+    let formatted = format!("{number:.3}");
+    formatted
+        .trim_end_matches('0')
+        .trim_end_matches('.')
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chart(data: &str, arguments: Value) -> Result<Chart, Error> {
+        let table = CsvTable::from_arguments(data, &json!({"format": "csv"}))
+            .ok()
+            .unwrap()
+            .unwrap();
+        Chart::new(table, &arguments)
+    }
+
+    fn csv_error(result: Result<Chart, Error>) -> String {
+        match result {
+            Err(Error::Csv(message)) => message,
+            _ => panic!("expected an error"),
+        }
+    }
+
+    #[test]
+    fn picks_nice_ticks() {
+        assert_eq!(nice_ticks(0.0, 10.0), [0.0, 2.0, 4.0, 6.0, 8.0, 10.0]);
+        assert_eq!(nice_ticks(0.0, 7.0), [0.0, 2.0, 4.0, 6.0, 8.0]);
+        assert_eq!(nice_ticks(-3.0, 3.0), [-4.0, -2.0, 0.0, 2.0, 4.0]);
+        assert_eq!(
+            nice_ticks(0.0, 0.4),
+            [0.0, 0.1, 0.2, 0.30000000000000004, 0.4]
+        );
+        // a range without any width is widened
+        assert_eq!(nice_ticks(5.0, 5.0), [4.0, 4.5, 5.0, 5.5, 6.0]);
+    }
+
+    #[test]
+    fn reads_series() {
+        let chart = chart("year,a,b\n2020,1,\n2021,n/a,2.5\n", json!({"type": "line"}))
+            .ok()
+            .unwrap();
+        assert_eq!(chart.categories, ["2020", "2021"]);
+        assert_eq!(chart.series.len(), 2);
+        assert_eq!(chart.series[0].name, "a");
+        assert_eq!(chart.series[0].values, [Some(1.0), None]);
+        assert_eq!(chart.series[1].values, [None, Some(2.5)]);
+        assert_eq!(chart.numeric_x(), Some(vec![2020.0, 2021.0]));
+    }
+
+    #[test]
+    fn rejects_non_finite_values() {
+        for value in ["inf", "-inf", "NaN"] {
+            let data = format!("x,y\na,1\nb,{value}\n");
+            assert_eq!(
+                csv_error(chart(&data, json!({}))),
+                format!("The value '{value}' of the series 'y' is not a finite number")
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_scatter_plots_without_numeric_x_values() {
+        assert_eq!(
+            csv_error(chart("x,y\n1,2\nthree,4\n", json!({"type": "scatter"}))),
+            "The x value 'three' of a scatter plot is not a finite number"
+        );
+        assert!(chart("x,y\n1,2\ninf,4\n", json!({"type": "scatter"})).is_err());
+        assert!(chart("x,y\n1,2\n3,4\n", json!({"type": "scatter"})).is_ok());
+        // a line chart falls back to categories
+        assert!(chart("x,y\none,2\n", json!({"type": "line"})).is_ok());
+    }
+
+    #[test]
+    fn rejects_bar_charts_with_several_series() {
+        assert!(chart("x,a,b\np,1,2\n", json!({"type": "bar"})).is_err());
+        assert!(chart("x,a,b\np,1,2\n", json!({"type": "grouped-bar"})).is_ok());
+    }
+
+    #[test]
+    fn rejects_charts_without_data() {
+        assert!(chart("x\na\n", json!({})).is_err());
+        assert!(chart("x,y\n", json!({})).is_err());
+    }
+
+    #[test]
+    fn draws_an_svg() {
+        let svg = chart("x,y\n<a>,1\nb,-2\n", json!({"y-label": "R&D"}))
+            .ok()
+            .unwrap()
+            .to_svg();
+        assert!(svg.starts_with("<svg"));
+        assert!(svg.contains("&lt;a&gt;"));
+        assert!(svg.contains("R&amp;D"));
+        assert!(svg_to_pgf(&svg).is_ok());
+    }
+}
@@ -0,0 +1,879 @@
+use crate::bibtex::{BibEntry, Name};
+use std::env;
+
+/// How citations and the bibliography are formatted, chosen using the
+/// constant `citation_style`. LaTeX uses biblatex with the same style, so
+/// that every format looks the same.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) enum CitationStyle {
+    Ieee,
+    Apa,
+    Harvard,
+    Vancouver,
+}
+
+/// A part of a formatted reference. Titles may contain modules, such as
+/// `[tex]()`, so they are parsed as inline content.
+pub(crate) enum Part {
+    Text(String),
+    Title(String),
+    Italic(String),
+    Url(String),
+}
+
+/// The kinds of sources that are formatted differently
+enum Kind {
+    Article,
+    /// A paper in proceedings or a chapter in a collection
+    InCollection,
+    Book,
+    Thesis,
+    Report,
+    /// Web pages, software and anything else
+    Other,
+}
+
+impl Kind {
+    fn of(source: &BibEntry) -> Self {
+        match source.kind.as_str() {
+            "article" => Kind::Article,
+            "inproceedings" | "incollection" | "inbook" | "conference" => Kind::InCollection,
+            "book" => Kind::Book,
+            "mastersthesis" | "phdthesis" | "thesis" => Kind::Thesis,
+            "report" | "techreport" => Kind::Report,
+            _ => Kind::Other,
+        }
+    }
+}
+
+impl CitationStyle {
+    pub(crate) fn from_env() -> Self {
+        let Ok(style) = env::var("citation_style") else {
+            return CitationStyle::Ieee;
+        };
+
+        match style.trim().to_lowercase().as_str() {
+            "ieee" => CitationStyle::Ieee,
+            "apa" => CitationStyle::Apa,
+            "harvard" => CitationStyle::Harvard,
+            "vancouver" => CitationStyle::Vancouver,
+            _ => {
+                eprintln!(
+                    "Unknown citation style '{style}'. Use any of ieee, apa, harvard and vancouver."
+                );
+                CitationStyle::Ieee
+            }
+        }
+    }
+
+    /// The biblatex package, loaded with the options of the style
+    pub(crate) fn biblatex_import(&self) -> String {
+        let options = match self {
+            CitationStyle::Ieee => "style=ieee,urldate=comp",
+            CitationStyle::Apa => "style=apa",
+            CitationStyle::Harvard => {
+                "style=authoryear,giveninits=true,uniquename=init,maxcitenames=3,dashed=false"
+            }
+            CitationStyle::Vancouver => "style=vancouver",
+        };
+        format!("\\usepackage[backend=biber,{options},block=ragged]{{biblatex}}")
+    }
+
+    /// Changes to the biblatex style, added after it has been loaded
+    pub(crate) fn latex_patches(&self) -> &'static str {
+        match self {
+            // only print the date of online sources in parentheses when there is one
+            CitationStyle::Ieee => {
+                r"\xpatchbibdriver{online}
+  {\printtext[parens]{\usebibmacro{date}}}
+  {\iffieldundef{year}
+    {}
+    {\printtext[parens]{\usebibmacro{date}}}}
+  {}
+  {\typeout{There was an error patching biblatex-ieee (specifically, ieee.bbx's @online driver)}}
+"
+            }
+            _ => "",
+        }
+    }
+
+    /// Whether sources are cited by number, in the order they are first cited
+    pub(crate) fn numeric(&self) -> bool {
+        matches!(self, CitationStyle::Ieee | CitationStyle::Vancouver)
+    }
+
+    /// The biblatex command used to cite
+    pub(crate) fn cite_command(&self) -> &'static str {
+        if self.numeric() {
+            "\\cite"
+        } else {
+            "\\parencite"
+        }
+    }
+
+    /// Sort the cited sources in the order of the bibliography
+    pub(crate) fn sort(&self, sources: &mut [&BibEntry]) {
+        if !self.numeric() {
+            sources.sort_by_cached_key(|source| {
+                (
+                    self.citation_names(source).to_lowercase(),
+                    source.year().unwrap_or_default(),
+                    source.get("title").unwrap_or_default().to_lowercase(),
+                )
+            });
+        }
+    }
+
+    /// The letters added to the year of sources by the same authors in the
+    /// same year, such as "2019a" and "2019b", in the order of the bibliography
+    pub(crate) fn year_suffixes(&self, sources: &[&BibEntry]) -> Vec<String> {
+        if self.numeric() {
+            return vec![String::new(); sources.len()];
+        }
+
+        let labels: Vec<(String, Option<String>)> = sources
+            .iter()
+            .map(|source| (self.citation_names(source), source.year()))
+            .collect();
+        labels
+            .iter()
+            .enumerate()
+            .map(|(index, label)| {
+                let same: Vec<usize> = (0..labels.len())
+                    .filter(|other| &labels[*other] == label)
+                    .collect();
+                if same.len() < 2 {
+                    return String::new();
+                }
+                let position = same.iter().position(|other| *other == index).unwrap();
+                char::from(b'a' + (position % 26) as u8).to_string()
+            })
+            .collect()
+    }
+
+    /// The authors as they are written in a citation, such as "Knuth" or
+    /// "Pearce and Kelly"
+    pub(crate) fn citation_names(&self, source: &BibEntry) -> String {
+        let names = source.names("author");
+        let last: Vec<&str> = names
+            .iter()
+            .filter(|name| !name.is_others())
+            .map(|name| name.last.as_str())
+            .collect();
+        let and = if *self == CitationStyle::Apa {
+            "&"
+        } else {
+            "and"
+        };
+
+        match last.as_slice() {
+            [] => source.get("title").unwrap_or_default(),
+            [name] if names.len() == 1 => name.to_string(),
+            [first, second] if names.len() == 2 => format!("{first} {and} {second}"),
+            [first, second, third] if names.len() == 3 && *self == CitationStyle::Harvard => {
+                format!("{first}, {second} and {third}")
+            }
+            [first, ..] => format!("{first} et al."),
+        }
+    }
+
+    /// The label of an author-year citation, such as "Knuth, 1984"
+    pub(crate) fn author_year(&self, source: &BibEntry, suffix: &str) -> String {
+        let year = source.year().unwrap_or_else(|| "n.d.".to_string());
+        let separator = if *self == CitationStyle::Apa {
+            ", "
+        } else {
+            " "
+        };
+        format!("{}{separator}{year}{suffix}", self.citation_names(source))
+    }
+
+    /// Format a source for the bibliography, where `suffix` is added to the year
+    pub(crate) fn format(&self, source: &BibEntry, suffix: &str) -> Vec<Part> {
+        let mut reference = Reference::default();
+        match self {
+            CitationStyle::Ieee => format_ieee(&mut reference, source),
+            CitationStyle::Apa => format_apa(&mut reference, source, suffix),
+            CitationStyle::Harvard => format_harvard(&mut reference, source, suffix),
+            CitationStyle::Vancouver => format_vancouver(&mut reference, source),
+        }
+
+        let mut parts = reference.0;
+        if let Some(Part::Text(text)) = parts.last_mut() {
+            text.truncate(text.trim_end().len());
+        }
+        parts
+    }
+}
+
+#[derive(Default)]
+struct Reference(Vec<Part>);
+
+impl Reference {
+    fn text(&mut self, text: impl Into<String>) {
+        // empty text is skipped, so optional fields can be added using unwrap_or_default
+        let text = text.into();
+        if !text.is_empty() {
+            self.0.push(Part::Text(text));
+        }
+    }
+
+    fn title(&mut self, title: &str) {
+        self.0.push(Part::Title(title.to_string()));
+    }
+
+    fn italic(&mut self, text: &str) {
+        self.0.push(Part::Italic(text.to_string()));
+    }
+
+    fn url(&mut self, url: &str) {
+        self.0.push(Part::Url(url.to_string()));
+    }
+
+    /// Add segments separated by `separator` and ended by `end`
+    fn join(&mut self, segments: Vec<Reference>, separator: &str, end: &str) {
+        let count = segments.len();
+        for (index, segment) in segments.into_iter().enumerate() {
+            self.0.extend(segment.0);
+            self.text(if index + 1 == count { end } else { separator });
+        }
+    }
+}
+
+fn text(text: impl Into<String>) -> Reference {
+    let mut reference = Reference::default();
+    reference.text(text);
+    reference
+}
+
+fn italic(text: &str) -> Reference {
+    let mut reference = Reference::default();
+    reference.italic(text);
+    reference
+}
+
+/// Format a source like biblatex-ieee does
+fn format_ieee(reference: &mut Reference, source: &BibEntry) {
+    let get = |field: &str| source.get(field);
+    let kind = Kind::of(source);
+
+    let authors = source.names("author");
+    if !authors.is_empty() {
+        reference.text(format!(
+            "{}, ",
+            list_names(&authors, 6, ", ", ", and ", " and ", |name| {
+                initials_first(name, " ")
+            })
+        ));
+    }
+
+    let date = match (get("month"), source.year()) {
+        (Some(month), Some(year)) => Some(format!("{} {year}", month_abbreviation(&month))),
+        (None, year) => year,
+        (Some(_), None) => None,
+    };
+
+    let mut segments: Vec<Reference> = Vec::new();
+    match kind {
+        Kind::Article => {
+            segments.extend(get("journal").map(|journal| italic(&journal)));
+            segments.extend(get("volume").map(|volume| text(format!("vol. {volume}"))));
+            segments.extend(get("number").map(|number| text(format!("no. {number}"))));
+            segments.extend(get("pages").map(|pages| text(pages_abbreviated(&pages))));
+            segments.extend(date.map(text));
+        }
+        Kind::InCollection => {
+            if let Some(booktitle) = get("booktitle") {
+                let mut segment = text("in ");
+                segment.italic(&booktitle);
+                segments.push(segment);
+            }
+            segments.extend(get("publisher").map(text));
+            segments.extend(date.map(text));
+            segments.extend(get("pages").map(|pages| text(pages_abbreviated(&pages))));
+        }
+        Kind::Book => {
+            segments.extend(edition(source).map(|edition| text(format!("{edition} ed."))));
+            segments.extend(get("publisher").map(text));
+            segments.extend(date.map(text));
+        }
+        Kind::Thesis => {
+            segments.push(text(thesis_type(
+                source,
+                "M.S. thesis",
+                "Ph.D. dissertation",
+            )));
+            segments.extend(get("school").or_else(|| get("institution")).map(text));
+            segments.extend(date.map(text));
+        }
+        Kind::Report => {
+            segments.extend(get("institution").map(text));
+            segments.extend(get("type").map(text));
+            segments.extend(get("number").map(|number| text(format!("no. {number}"))));
+            segments.extend(date.map(text));
+        }
+        Kind::Other => {
+            segments.extend(publisher(source).map(text));
+            segments.extend(get("version").map(|version| text(format!("ver. {version}"))));
+            segments.extend(date.map(text));
+        }
+    }
+    if let Some(doi) = source.verbatim("doi") {
+        segments.push(text(format!("doi: {doi}")));
+    }
+
+    // the punctuation after a quoted title goes within the quotes
+    let title = get("title").unwrap_or_default();
+    let end = if segments.is_empty() { "." } else { "," };
+    match source.kind.as_str() {
+        "book" | "online" | "manual" => {
+            reference.italic(&title);
+            reference.text(end);
+        }
+        _ => {
+            reference.text("“");
+            reference.title(&title);
+            reference.text(format!("{end}”"));
+        }
+    }
+    if !segments.is_empty() {
+        reference.text(" ");
+        reference.join(segments, ", ", ".");
+    }
+
+    if let Some(url) = source.verbatim("url") {
+        reference.text(" [Online]. Available: ");
+        reference.url(&url);
+        if let Some(visited) = get("urldate") {
+            reference.text(format!(" (visited on {}).", date_ieee(&visited)));
+        }
+    }
+}
+
+/// Format a source like the 7th edition of the APA style
+fn format_apa(reference: &mut Reference, source: &BibEntry, suffix: &str) {
+    let get = |field: &str| source.get(field);
+    let kind = Kind::of(source);
+    let title = get("title").unwrap_or_default();
+
+    let mut date = source.year().unwrap_or_else(|| "n.d.".to_string()) + suffix;
+    if let (Kind::Other, Some(month)) = (&kind, get("month")) {
+        date = format!("{date}, {}", month_name(&month));
+        if let Some(day) = get("day") {
+            date = format!("{date} {day}");
+        }
+    }
+
+    // the title takes the place of missing authors
+    let authors = source.names("author");
+    let title_first = authors.is_empty();
+    if title_first {
+        reference.italic(&title);
+        reference.text(". ");
+    } else {
+        reference.text(format!(
+            "{} ",
+            list_names(&authors, 20, ", ", ", & ", ", & ", |name| {
+                last_first(name, ", ", " ")
+            })
+        ));
+    }
+    reference.text(format!("({date}). "));
+
+    if !title_first {
+        match kind {
+            Kind::Article | Kind::InCollection => reference.title(&title),
+            _ => reference.italic(&title),
+        }
+        if let (Kind::Book, Some(edition)) = (&kind, edition(source)) {
+            reference.text(format!(" ({edition} ed.)"));
+        }
+        if let Kind::Thesis = kind {
+            let school = get("school").or_else(|| get("institution"));
+            let thesis = thesis_type(source, "Master's thesis", "Doctoral dissertation");
+            match school {
+                Some(school) => reference.text(format!(" [{thesis}, {school}]")),
+                None => reference.text(format!(" [{thesis}]")),
+            }
+        }
+        reference.text(". ");
+    }
+
+    match kind {
+        Kind::Article => {
+            if let Some(journal) = get("journal") {
+                reference.italic(&journal);
+                if let Some(volume) = get("volume") {
+                    reference.text(", ");
+                    reference.italic(&volume);
+                }
+                if let Some(number) = get("number") {
+                    reference.text(format!("({number})"));
+                }
+                if let Some(pages) = get("pages") {
+                    reference.text(format!(", {pages}"));
+                }
+                reference.text(". ");
+            }
+        }
+        Kind::InCollection => {
+            if let Some(booktitle) = get("booktitle") {
+                reference.text("In ");
+                reference.italic(&booktitle);
+                if let Some(pages) = get("pages") {
+                    reference.text(format!(" ({})", pages_abbreviated(&pages)));
+                }
+                reference.text(". ");
+            }
+            reference.text(
+                get("publisher")
+                    .map(|publisher| format!("{publisher}. "))
+                    .unwrap_or_default(),
+            );
+        }
+        Kind::Report => {
+            reference.text(
+                get("institution")
+                    .map(|institution| format!("{institution}. "))
+                    .unwrap_or_default(),
+            );
+        }
+        Kind::Book | Kind::Other => {
+            reference.text(
+                publisher(source)
+                    .map(|publisher| format!("{publisher}. "))
+                    .unwrap_or_default(),
+            );
+        }
+        Kind::Thesis => {}
+    }
+
+    if let Some(doi) = source.verbatim("doi") {
+        reference.url(&format!("https://doi.org/{doi}"));
+    } else if let Some(url) = source.verbatim("url") {
+        reference.url(&url);
+    }
+}
+
+/// Format a source like the Harvard style of Cite Them Right
+fn format_harvard(reference: &mut Reference, source: &BibEntry, suffix: &str) {
+    let get = |field: &str| source.get(field);
+    let kind = Kind::of(source);
+    let title = get("title").unwrap_or_default();
+    let date = source.year().unwrap_or_else(|| "no date".to_string()) + suffix;
+
+    let authors = source.names("author");
+    if authors.is_empty() {
+        reference.italic(&title);
+    } else {
+        let names: Vec<&Name> = authors.iter().filter(|name| !name.is_others()).collect();
+        let format = |name: &Name| last_first(name, ", ", "");
+        if names.len() > 3 || names.len() < authors.len() {
+            let first = names.first().map(|name| format(name)).unwrap_or_default();
+            reference.text(format!("{first} et al."));
+        } else {
+            reference.text(list_names(&authors, 3, ", ", " and ", " and ", format));
+        }
+    }
+    reference.text(format!(" ({date}) "));
+
+    if !authors.is_empty() {
+        match kind {
+            Kind::Article | Kind::InCollection => {
+                reference.text("‘");
+                reference.title(&title);
+                reference.text("’");
+            }
+            _ => reference.italic(&title),
+        }
+    }
+
+    let rest = reference.0.len();
+    match kind {
+        Kind::Article => {
+            if let Some(journal) = get("journal") {
+                reference.text(", ");
+                reference.italic(&journal);
+            }
+            let volume = match (get("volume"), get("number")) {
+                (Some(volume), Some(number)) => Some(format!("{volume}({number})")),
+                (volume, _) => volume,
+            };
+            reference.text(
+                volume
+                    .map(|volume| format!(", {volume}"))
+                    .unwrap_or_default(),
+            );
+            reference.text(
+                get("pages")
+                    .map(|pages| format!(", {}", pages_abbreviated(&pages)))
+                    .unwrap_or_default(),
+            );
+            reference.text(". ");
+        }
+        Kind::InCollection => {
+            if let Some(booktitle) = get("booktitle") {
+                reference.text(", in ");
+                reference.italic(&booktitle);
+            }
+            reference.text(". ");
+            reference.text(
+                get("publisher")
+                    .map(|publisher| format!("{publisher}, "))
+                    .unwrap_or_default(),
+            );
+            reference.text(
+                get("pages")
+                    .map(|pages| format!("{}. ", pages_abbreviated(&pages)))
+                    .unwrap_or_default(),
+            );
+        }
+        Kind::Book => {
+            reference.text(". ");
+            reference.text(
+                edition(source)
+                    .map(|edition| format!("{edition} edn. "))
+                    .unwrap_or_default(),
+            );
+            reference.text(
+                get("publisher")
+                    .map(|publisher| format!("{publisher}. "))
+                    .unwrap_or_default(),
+            );
+        }
+        Kind::Thesis => {
+            reference.text(format!(
+                ". {}. ",
+                thesis_type(source, "Master's thesis", "PhD thesis")
+            ));
+            reference.text(
+                get("school")
+                    .or_else(|| get("institution"))
+                    .map(|school| format!("{school}. "))
+                    .unwrap_or_default(),
+            );
+        }
+        Kind::Report => {
+            reference.text(". ");
+            reference.text(
+                get("institution")
+                    .map(|institution| format!("{institution}. "))
+                    .unwrap_or_default(),
+            );
+        }
+        Kind::Other => {
+            reference.text(". ");
+            reference.text(
+                publisher(source)
+                    .map(|publisher| format!("{publisher}. "))
+                    .unwrap_or_default(),
+            );
+        }
+    }
+
+    // the title is already written in place of the authors
+    if authors.is_empty() {
+        if let Some(Part::Text(text)) = reference.0.get_mut(rest) {
+            *text = text.trim_start_matches([',', '.', ' ']).to_string();
+        }
+    }
+
+    let url = source
+        .verbatim("doi")
+        .map(|doi| format!("https://doi.org/{doi}"))
+        .or_else(|| source.verbatim("url"));
+    if let Some(url) = url {
+        reference.text("Available at: ");
+        reference.url(&url);
+        match get("urldate") {
+            Some(accessed) => reference.text(format!(" (Accessed: {}).", date_harvard(&accessed))),
+            None => reference.text("."),
+        }
+    }
+}
+
+/// Format a source like the Vancouver style of the NLM
+fn format_vancouver(reference: &mut Reference, source: &BibEntry) {
+    let get = |field: &str| source.get(field);
+    let kind = Kind::of(source);
+    let year = source.year().unwrap_or_default();
+
+    let authors = source.names("author");
+    if !authors.is_empty() {
+        let names: Vec<&Name> = authors.iter().filter(|name| !name.is_others()).collect();
+        let format = |name: &Name| {
+            let initials: String = name
+                .initials()
+                .chars()
+                .filter(|c| c.is_alphabetic())
+                .collect();
+            if initials.is_empty() {
+                name.last.clone()
+            } else {
+                format!("{} {initials}", name.last)
+            }
+        };
+        let mut list: Vec<String> = names.iter().take(6).map(|name| format(name)).collect();
+        if names.len() > 6 || names.len() < authors.len() {
+            list.push("et al".to_string());
+        }
+        reference.text(format!("{}. ", list.join(", ")));
+    }
+
+    reference.title(&get("title").unwrap_or_default());
+    let medium = match kind {
+        Kind::Thesis => format!(
+            " [{}]",
+            thesis_type(source, "master's thesis", "dissertation")
+        ),
+        Kind::Other if source.verbatim("url").is_some() => " [Internet]".to_string(),
+        _ => String::new(),
+    };
+    reference.text(format!("{medium}. "));
+
+    match kind {
+        Kind::Article => {
+            reference.text(
+                get("journal")
+                    .map(|journal| format!("{journal}. "))
+                    .unwrap_or_default(),
+            );
+            reference.text(year);
+            reference.text(
+                get("volume")
+                    .map(|volume| format!(";{volume}"))
+                    .unwrap_or_default(),
+            );
+            reference.text(
+                get("number")
+                    .map(|number| format!("({number})"))
+                    .unwrap_or_default(),
+            );
+            reference.text(
+                get("pages")
+                    .map(|pages| format!(":{pages}"))
+                    .unwrap_or_default(),
+            );
+        }
+        Kind::InCollection => {
+            reference.text(
+                get("booktitle")
+                    .map(|booktitle| format!("In: {booktitle}. "))
+                    .unwrap_or_default(),
+            );
+            reference.text(
+                get("publisher")
+                    .map(|publisher| format!("{publisher}; "))
+                    .unwrap_or_default(),
+            );
+            reference.text(year);
+            reference.text(
+                get("pages")
+                    .map(|pages| format!(". p. {pages}"))
+                    .unwrap_or_default(),
+            );
+        }
+        Kind::Book => {
+            reference.text(
+                edition(source)
+                    .map(|edition| format!("{edition} ed. "))
+                    .unwrap_or_default(),
+            );
+            reference.text(
+                get("publisher")
+                    .map(|publisher| format!("{publisher}; "))
+                    .unwrap_or_default(),
+            );
+            reference.text(year);
+        }
+        Kind::Thesis => {
+            reference.text(
+                get("school")
+                    .or_else(|| get("institution"))
+                    .map(|school| format!("{school}; "))
+                    .unwrap_or_default(),
+            );
+            reference.text(year);
+        }
+        Kind::Report => {
+            reference.text(
+                get("institution")
+                    .map(|institution| format!("{institution}; "))
+                    .unwrap_or_default(),
+            );
+            reference.text(year);
+        }
+        Kind::Other => {
+            reference.text(
+                publisher(source)
+                    .map(|publisher| format!("{publisher}; "))
+                    .unwrap_or_default(),
+            );
+            reference.text(year);
+        }
+    }
+    if let Some(cited) = get("urldate") {
+        reference.text(format!(" [cited {}]", date_vancouver(&cited)));
+    }
+    reference.text(".");
+
+    if let Some(doi) = source.verbatim("doi") {
+        reference.text(format!(" doi: {doi}."));
+    }
+    if let Some(url) = source.verbatim("url") {
+        reference.text(" Available from: ");
+        reference.url(&url);
+    }
+}
+
+/// List names, where more than `max` names are cut short using "et al."
+fn list_names(
+    names: &[Name],
+    max: usize,
+    separator: &str,
+    last_separator: &str,
+    pair_separator: &str,
+    format: impl Fn(&Name) -> String,
+) -> String {
+    let others = names.len() > max || names.iter().any(Name::is_others);
+    let names: Vec<String> = names
+        .iter()
+        .filter(|name| !name.is_others())
+        .take(if names.len() > max { 1 } else { names.len() })
+        .map(format)
+        .collect();
+
+    match names.as_slice() {
+        _ if others => format!("{} et al.", names.join(separator)),
+        [name] => name.clone(),
+        [first, second] => format!("{first}{pair_separator}{second}"),
+        [rest @ .., last] => format!("{}{last_separator}{last}", rest.join(separator)),
+        [] => String::new(),
+    }
+}
+
+/// A name written as "A. B. Last"
+fn initials_first(name: &Name, space: &str) -> String {
+    let initials = name.initials().replace(' ', space);
+    if initials.is_empty() {
+        name.last.clone()
+    } else {
+        format!("{initials} {}", name.last)
+    }
+}
+
+/// A name written as "Last, A. B."
+fn last_first(name: &Name, comma: &str, space: &str) -> String {
+    let initials = name.initials().replace(' ', space);
+    if initials.is_empty() {
+        name.last.clone()
+    } else {
+        format!("{}{comma}{initials}", name.last)
+    }
+}
+
+fn publisher(source: &BibEntry) -> Option<String> {
+    source
+        .get("howpublished")
+        .or_else(|| source.get("organization"))
+        .or_else(|| source.get("publisher"))
+}
+
+fn thesis_type(source: &BibEntry, masters: &str, doctoral: &str) -> String {
+    match source.kind.as_str() {
+        "mastersthesis" => masters.to_string(),
+        "phdthesis" => doctoral.to_string(),
+        _ => source.get("type").unwrap_or_else(|| "Thesis".to_string()),
+    }
+}
+
+fn pages_abbreviated(pages: &str) -> String {
+    if pages.contains(['–', '-']) {
+        format!("pp. {pages}")
+    } else {
+        format!("p. {pages}")
+    }
+}
+
+/// The edition, where a number such as "2" is written as "2nd"
+fn edition(source: &BibEntry) -> Option<String> {
+    let edition = source.get("edition")?;
+    let Ok(number) = edition.parse::<u32>() else {
+        return Some(edition);
+    };
+
+    let suffix = match (number % 10, number % 100) {
+        (_, 11..=13) => "th",
+        (1, _) => "st",
+        (2, _) => "nd",
+        (3, _) => "rd",
+        _ => "th",
+    };
+    Some(format!("{number}{suffix}"))
+}
+
+const MONTHS: [&str; 12] = [
+    "January",
+    "February",
+    "March",
+    "April",
+    "May",
+    "June",
+    "July",
+    "August",
+    "September",
+    "October",
+    "November",
+    "December",
+];
+
+fn month_name(month: &str) -> String {
+    match month.trim_start_matches('0').parse::<usize>() {
+        Ok(number @ 1..=12) => MONTHS[number - 1].to_string(),
+        _ => month.to_string(),
+    }
+}
+
+/// A month written as "Jan.", like biblatex does
+fn month_abbreviation(month: &str) -> String {
+    let name = month_name(month);
+    match name.as_str() {
+        "May" => name,
+        _ if MONTHS.contains(&name.as_str()) => format!("{}.", &name[..3]),
+        _ => name,
+    }
+}
+
+/// Split a date such as "2023-01-23" into its year, month and day
+fn split_date(date: &str) -> Option<(&str, &str, &str)> {
+    let mut parts = date.split('-');
+    let date = (parts.next()?, parts.next()?, parts.next()?);
+    Some((date.0, date.1, date.2.trim_start_matches('0')))
+}
+
+/// Write a date as "Jan. 23, 2023"
+fn date_ieee(date: &str) -> String {
+    match split_date(date) {
+        Some((year, month, day)) => format!("{} {day}, {year}", month_abbreviation(month)),
+        None => date.to_string(),
+    }
+}
+
+/// Write a date as "23 January 2023"
+fn date_harvard(date: &str) -> String {
+    match split_date(date) {
+        Some((year, month, day)) => format!("{day} {} {year}", month_name(month)),
+        None => date.to_string(),
+    }
+}
+
+/// Write a date as "2023 Jan 23"
+fn date_vancouver(date: &str) -> String {
+    match split_date(date) {
+        Some((year, month, day)) => {
+            let month: String = month_name(month).chars().take(3).collect();
+            format!("{year} {month} {day}")
+        }
+        None => date.to_string(),
+    }
+}
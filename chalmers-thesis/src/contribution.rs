@@ -0,0 +1,318 @@
+use crate::{
+    escape::{escape_html, escape_latex},
+    DocSettings, Error,
+};
+use serde_json::{json, Value};
+use std::env;
+
+/// Transform `[contribution]`, which records what an author has done. The
+/// content of the module is an optional statement in the author's own words.
+pub(crate) fn transform_contribution(input: Value, _to: &str) -> Result<String, Error> {
+    let arguments = &input["arguments"];
+    let author = arguments["author"].as_str().unwrap_or_default().trim();
+
+    let entry = json!({
+        "author": author,
+        "sections": split_list(arguments["sections"].as_str().unwrap_or_default()),
+        "implementation": split_list(arguments["implementation"].as_str().unwrap_or_default()),
+        "text": input["data"].as_str().unwrap_or_default().trim(),
+    });
+
+    let json = json!([
+        {"name": "list-push", "arguments": {"name": "contributions"}, "data": entry.to_string()}
+    ]);
+    Ok(serde_json::to_string(&json).unwrap())
+}
+
+/// Transform `[written-by]`, which notes the authors of a chapter or section.
+/// These notes make up the matrix of sections and authors in the report.
+pub(crate) fn transform_written_by(input: Value, _to: &str) -> Result<String, Error> {
+    let section = input["arguments"]["section"].as_str().unwrap_or_default();
+
+    let entry = json!({
+        "section": section.trim(),
+        "authors": split_list(input["data"].as_str().unwrap_or_default()),
+    });
+
+    let json = json!([
+        {"name": "list-push", "arguments": {"name": "contributions"}, "data": entry.to_string()}
+    ]);
+    Ok(serde_json::to_string(&json).unwrap())
+}
+
+fn split_list(list: &str) -> Vec<String> {
+    list.split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// What a single author has contributed, gathered from all `[contribution]`
+/// and `[written-by]` modules naming them
+#[derive(Default)]
+struct AuthorContribution {
+    name: String,
+    /// Used to find the author by e-mail address as well as by name
+    email: Option<String>,
+    programme: Option<String>,
+    sections: Vec<String>,
+    implementation: Vec<String>,
+    statements: Vec<String>,
+}
+
+impl AuthorContribution {
+    /// The name of the author, followed by their programme if it is known
+    fn heading(&self) -> String {
+        match &self.programme {
+            Some(programme) => format!("{} ({programme})", self.name),
+            None => self.name.clone(),
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.sections.is_empty() && self.implementation.is_empty() && self.statements.is_empty()
+    }
+}
+
+/// The contribution report, made up of a part for every author and a matrix
+/// of which sections each author has written
+pub(crate) struct ContributionReport {
+    authors: Vec<AuthorContribution>,
+    /// Every annotated section with the indices of its authors, in the order
+    /// they occur
+    sections: Vec<(String, Vec<usize>)>,
+}
+
+impl ContributionReport {
+    /// Read the contributions made so far. Returns `None` if there aren't any.
+    pub(crate) fn from_env(settings: &DocSettings) -> Option<Self> {
+        let var = env::var("contributions").unwrap_or_else(|_| "[]".to_string());
+        let entries: Vec<String> = serde_json::from_str(&var).unwrap();
+        if entries.is_empty() {
+            return None;
+        }
+
+        // the authors of the thesis come first, in the order they are listed
+        let mut authors: Vec<AuthorContribution> = settings
+            .authors
+            .iter()
+            .map(|author| AuthorContribution {
+                name: author.name.clone(),
+                email: author.email.clone(),
+                programme: author.programme.clone(),
+                ..Default::default()
+            })
+            .collect();
+        let mut sections: Vec<(String, Vec<usize>)> = Vec::new();
+
+        /// Find an author by name or e-mail address, adding them if needed
+        fn author(authors: &mut Vec<AuthorContribution>, name: &str) -> usize {
+            match authors
+                .iter()
+                .position(|author| author.name == name || author.email.as_deref() == Some(name))
+            {
+                Some(index) => index,
+                None => {
+                    eprintln!("'{name}' has contributions, but is not one of the authors.");
+                    authors.push(AuthorContribution {
+                        name: name.to_string(),
+                        ..Default::default()
+                    });
+                    authors.len() - 1
+                }
+            }
+        }
+
+        for entry in entries {
+            let entry: Value = serde_json::from_str(&entry).unwrap();
+            let list = |key: &str| -> Vec<String> {
+                entry[key]
+                    .as_array()
+                    .map(|items| {
+                        items
+                            .iter()
+                            .filter_map(|item| item.as_str().map(str::to_string))
+                            .collect()
+                    })
+                    .unwrap_or_default()
+            };
+
+            if let Some(section) = entry["section"].as_str() {
+                let mut indices = Vec::new();
+                for name in list("authors") {
+                    let index = author(&mut authors, &name);
+                    let author = &mut authors[index];
+                    if !author.sections.iter().any(|s| s == section) {
+                        author.sections.push(section.to_string());
+                    }
+                    if !indices.contains(&index) {
+                        indices.push(index);
+                    }
+                }
+                sections.push((section.to_string(), indices));
+                continue;
+            }
+
+            let name = entry["author"].as_str().unwrap_or_default();
+            if name.is_empty() {
+                eprintln!("A contribution is missing the name of its author.");
+                continue;
+            }
+            let index = author(&mut authors, name);
+            let author = &mut authors[index];
+            for section in list("sections") {
+                if !author.sections.contains(&section) {
+                    author.sections.push(section);
+                }
+            }
+            author.implementation.extend(list("implementation"));
+            let text = entry["text"].as_str().unwrap_or_default();
+            if !text.is_empty() {
+                author.statements.push(text.to_string());
+            }
+        }
+
+        Some(Self { authors, sections })
+    }
+
+    /// Render the report in front matter, after the acknowledgements, or as an
+    /// unnumbered chapter at the end of the document
+    pub(crate) fn to_latex(&self, appendix: bool) -> Vec<Value> {
+        let mut content = Vec::new();
+
+        content.push(json!(if appendix {
+            "\n\\chapter*{Contribution report}\n\\addcontentsline{toc}{chapter}{Contribution report}\n"
+        } else {
+            "\\newpage\n\\thispagestyle{plain}\n\n\\section*{Contribution report}\n"
+        }));
+
+        let heading = if appendix {
+            "\\section*"
+        } else {
+            "\\subsection*"
+        };
+        for author in self.authors.iter().filter(|author| !author.is_empty()) {
+            content.push(json!(format!(
+                "{heading}{{{}}}\n",
+                escape_latex(&author.heading())
+            )));
+            if !author.sections.is_empty() {
+                content.push(json!("\\textit{Sections:} "));
+                content.push(json!({"name": "inline_content", "data": author.sections.join(", ")}));
+                content.push(json!("\n\n"));
+            }
+            if !author.implementation.is_empty() {
+                content.push(json!("\\textit{Implementation:} "));
+                content.push(
+                    json!({"name": "inline_content", "data": author.implementation.join(", ")}),
+                );
+                content.push(json!("\n\n"));
+            }
+            for statement in &author.statements {
+                content.push(json!({"name": "block_content", "data": statement}));
+                content.push(json!("\n\n"));
+            }
+        }
+
+        if !self.sections.is_empty() {
+            let columns = self.matrix_authors();
+            content.push(json!(format!(
+                "{heading}{{Sections by author}}\n\\begin{{center}}\n\\begin{{tabular}}{{l{}}}\n\\hline\nSection",
+                "c".repeat(columns.len())
+            )));
+            for &index in &columns {
+                content.push(json!(format!(
+                    " & {}",
+                    escape_latex(&self.authors[index].name)
+                )));
+            }
+            content.push(json!("\\\\\n\\hline\n"));
+            for (section, authors) in &self.sections {
+                content.push(json!({"name": "inline_content", "data": section}));
+                for index in &columns {
+                    content.push(json!(if authors.contains(index) {
+                        " & \\checkmark"
+                    } else {
+                        " & "
+                    }));
+                }
+                content.push(json!("\\\\\n"));
+            }
+            content.push(json!("\\hline\n\\end{tabular}\n\\end{center}\n"));
+        }
+
+        content
+    }
+
+    pub(crate) fn to_html(&self) -> Vec<Value> {
+        let mut content = vec![json!(
+            r#"<div class="contribution-report"><h2>Contribution report</h2>"#
+        )];
+
+        for author in self.authors.iter().filter(|author| !author.is_empty()) {
+            content.push(json!(format!(
+                "<h3>{}</h3>",
+                escape_html(&author.heading())
+            )));
+            if !author.sections.is_empty() {
+                content.push(json!("<p><em>Sections:</em> "));
+                content.push(json!({"name": "inline_content", "data": author.sections.join(", ")}));
+                content.push(json!("</p>"));
+            }
+            if !author.implementation.is_empty() {
+                content.push(json!("<p><em>Implementation:</em> "));
+                content.push(
+                    json!({"name": "inline_content", "data": author.implementation.join(", ")}),
+                );
+                content.push(json!("</p>"));
+            }
+            for statement in &author.statements {
+                content.push(json!({"name": "block_content", "data": statement}));
+            }
+        }
+
+        if !self.sections.is_empty() {
+            let columns = self.matrix_authors();
+            content.push(json!(
+                r#"<h3>Sections by author</h3><table class="contribution-matrix"><thead><tr><th>Section</th>"#
+            ));
+            for &index in &columns {
+                content.push(json!(format!(
+                    r#"<th scope="col">{}</th>"#,
+                    escape_html(&self.authors[index].name)
+                )));
+            }
+            content.push(json!("</tr></thead><tbody>"));
+            for (section, authors) in &self.sections {
+                content.push(json!(r#"<tr><th scope="row">"#));
+                content.push(json!({"name": "inline_content", "data": section}));
+                content.push(json!("</th>"));
+                for index in &columns {
+                    content.push(json!(if authors.contains(index) {
+                        r#"<td aria-label="yes">✓</td>"#
+                    } else {
+                        "<td></td>"
+                    }));
+                }
+                content.push(json!("</tr>"));
+            }
+            content.push(json!("</tbody></table>"));
+        }
+
+        content.push(json!("</div>"));
+        content
+    }
+
+    /// The indices of the authors that have written any of the annotated
+    /// sections, in the order the authors are listed
+    fn matrix_authors(&self) -> Vec<usize> {
+        (0..self.authors.len())
+            .filter(|index| {
+                self.sections
+                    .iter()
+                    .any(|(_, authors)| authors.contains(index))
+            })
+            .collect()
+    }
+}
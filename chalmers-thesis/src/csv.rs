@@ -0,0 +1,359 @@
+use crate::Error;
+use serde_json::Value;
+use std::{cmp::Ordering, fs};
+
+/// Arguments used to load a table from delimiter separated values, which are
+/// removed before the table is passed on to std `[table]`
+pub(crate) const CSV_ARGUMENTS: [&str; 7] = [
+    "file",
+    "format",
+    "columns",
+    "decimals",
+    "number-columns",
+    "sort",
+    "limit",
+];
+
+/// Delimiters that can be used when passing the table on to std `[table]`. The
+/// first one that does not occur in any cell is used.
+const TABLE_DELIMITERS: [&str; 5] = ["|", "\t", ";", "¦", "§"];
+
+/// A table read from a CSV or TSV source, where the first row is the header
+pub(crate) struct CsvTable {
+    pub(crate) rows: Vec<Vec<String>>,
+}
+
+impl CsvTable {
+    /// Parse delimiter separated values. Fields may be quoted using double
+    /// quotes, in which case they may contain the separator, newlines and
+    /// escaped quotes ("").
+    pub(crate) fn parse(text: &str, separator: char) -> Self {
+        let mut rows = Vec::new();
+        let mut row = Vec::new();
+        let mut field = String::new();
+        let mut quoted = false;
+        let mut chars = text.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            match c {
+                '"' if quoted => {
+                    if chars.peek() == Some(&'"') {
+                        field.push('"');
+                        chars.next();
+                    } else {
+                        quoted = false;
+                    }
+                }
+                '"' if field.is_empty() => quoted = true,
+                c if c == separator && !quoted => row.push(std::mem::take(&mut field)),
+                '\r' if !quoted => {}
+                '\n' if !quoted => {
+                    row.push(std::mem::take(&mut field));
+                    rows.push(std::mem::take(&mut row));
+                }
+                c => field.push(c),
+            }
+        }
+
+        if !field.is_empty() || !row.is_empty() {
+            row.push(field);
+            rows.push(row);
+        }
+
+        rows.retain(|row| row.iter().any(|field| !field.trim().is_empty()));
+        Self { rows }
+    }
+
+    /// Load a table from the given arguments, either from the file given by
+    /// `file` or from the content of the module. Returns `None` if the table
+    /// isn't given as CSV or TSV, and the content should be used as is.
+    pub(crate) fn from_arguments(data: &str, arguments: &Value) -> Result<Option<Self>, Error> {
+        let file = arguments["file"].as_str().unwrap_or_default().trim();
+        let format = arguments["format"].as_str().unwrap_or("auto");
+
+        let separator = match (format, file) {
+            ("csv", _) => ',',
+            ("tsv", _) => '\t',
+            (_, "") => return Ok(None),
+            (_, file) if file.ends_with(".tsv") || file.ends_with(".tab") => '\t',
+            _ => ',',
+        };
+
+        let text = if file.is_empty() {
+            data.to_string()
+        } else {
+            fs::read_to_string(file)
+                .map_err(|error| Error::Csv(format!("Could not read '{file}': {error}")))?
+        };
+
+        let mut table = Self::parse(&text, separator);
+
+        // sorted before the columns are selected, so that the rows may be
+        // sorted by a column that isn't shown
+        let sort = arguments["sort"].as_str().unwrap_or_default();
+        if !sort.trim().is_empty() {
+            table.sort(sort)?;
+        }
+
+        let columns = arguments["columns"].as_str().unwrap_or_default();
+        if !columns.trim().is_empty() {
+            table.select_columns(columns)?;
+        }
+
+        if let Some(limit) = arguments["limit"].as_u64().filter(|&limit| limit > 0) {
+            // the header is not counted
+            table.rows.truncate(limit as usize + 1);
+        }
+
+        if let Some(decimals) = arguments["decimals"]
+            .as_i64()
+            .filter(|&decimals| decimals >= 0)
+        {
+            let columns = arguments["number-columns"].as_str().unwrap_or_default();
+            if columns.trim().is_empty() {
+                eprintln!("'decimals' is only used for the columns given by 'number-columns'.");
+            } else {
+                table.format_numbers(columns, decimals as usize)?;
+            }
+        }
+
+        Ok(Some(table))
+    }
+
+    /// Find a column either by its name in the header or by its 1-based index
+    fn column_index(&self, column: &str) -> Result<usize, Error> {
+        let column = column.trim();
+        let header = self.rows.first().map(Vec::as_slice).unwrap_or_default();
+
+        header
+            .iter()
+            .position(|name| name.trim() == column)
+            .or_else(|| {
+                column
+                    .parse::<usize>()
+                    .ok()
+                    .filter(|&index| index >= 1 && index <= header.len())
+                    .map(|index| index - 1)
+            })
+            .ok_or_else(|| Error::Csv(format!("Unknown column '{column}'")))
+    }
+
+    /// Keep only the given comma separated columns, in the given order
+    fn select_columns(&mut self, columns: &str) -> Result<(), Error> {
+        let indices = columns
+            .split(',')
+            .map(|column| self.column_index(column))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        for row in &mut self.rows {
+            *row = indices
+                .iter()
+                .map(|&index| row.get(index).cloned().unwrap_or_default())
+                .collect();
+        }
+
+        Ok(())
+    }
+
+    /// Sort the rows below the header by a column, where numbers are sorted
+    /// numerically and come before text. A leading '-' sorts in descending
+    /// order.
+    fn sort(&mut self, sort: &str) -> Result<(), Error> {
+        let sort = sort.trim();
+        let (column, descending) = match sort.strip_prefix('-') {
+            Some(column) => (column, true),
+            None => (sort, false),
+        };
+        let index = self.column_index(column)?;
+
+        if self.rows.len() > 1 {
+            self.rows[1..].sort_by(|a, b| {
+                let a = a.get(index).map(|s| s.trim()).unwrap_or_default();
+                let b = b.get(index).map(|s| s.trim()).unwrap_or_default();
+                // a total order, since sort_by may panic otherwise
+                let ordering = match (a.parse::<f64>(), b.parse::<f64>()) {
+                    (Ok(a), Ok(b)) => a.total_cmp(&b),
+                    (Ok(_), Err(_)) => Ordering::Less,
+                    (Err(_), Ok(_)) => Ordering::Greater,
+                    (Err(_), Err(_)) => a.cmp(b),
+                };
+                if descending {
+                    ordering.reverse()
+                } else {
+                    ordering
+                }
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Round the numbers below the header in the given comma separated
+    /// columns to a fixed number of decimals. Other columns, such as years or
+    /// IDs, are kept as they are.
+    fn format_numbers(&mut self, columns: &str, decimals: usize) -> Result<(), Error> {
+        let indices = columns
+            .split(',')
+            .map(|column| self.column_index(column))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        for row in self.rows.iter_mut().skip(1) {
+            for &index in &indices {
+                let Some(cell) = row.get_mut(index) else {
+                    continue;
+                };
+                if let Ok(number) = cell.trim().parse::<f64>() {
+                    *cell = format!("{number:.decimals$}");
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Write the table in the format std `[table]` expects, returning the
+    /// content and the delimiter used between cells
+    pub(crate) fn to_table_content(&self) -> (String, &'static str) {
+        let delimiter = TABLE_DELIMITERS
+            .into_iter()
+            .find(|delimiter| {
+                !self
+                    .rows
+                    .iter()
+                    .flatten()
+                    .any(|cell| cell.contains(delimiter))
+            })
+            .unwrap_or("|");
+
+        let content = self
+            .rows
+            .iter()
+            .map(|row| {
+                row.iter()
+                    // cells in std [table] are a single line each
+                    .map(|cell| cell.replace('\n', " "))
+                    .collect::<Vec<_>>()
+                    .join(delimiter)
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        (content, delimiter)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn table(text: &str) -> CsvTable {
+        CsvTable::parse(text, ',')
+    }
+
+    fn column(table: &CsvTable, index: usize) -> Vec<&str> {
+        table.rows[1..]
+            .iter()
+            .map(|row| row[index].as_str())
+            .collect()
+    }
+
+    #[test]
+    fn parses_quoted_fields() {
+        let table = table("a,b\r\n\"x, y\",\"say \"\"hi\"\"\"\n\"two\nlines\",z\n");
+        assert_eq!(
+            table.rows,
+            vec![
+                vec!["a", "b"],
+                vec!["x, y", "say \"hi\""],
+                vec!["two\nlines", "z"],
+            ]
+        );
+    }
+
+    #[test]
+    fn parses_empty_input() {
+        assert!(table("").rows.is_empty());
+        assert!(table("\n , \n\n").rows.is_empty());
+    }
+
+    #[test]
+    fn keeps_last_row_without_newline() {
+        assert_eq!(table("a,b\n1,").rows, vec![vec!["a", "b"], vec!["1", ""]]);
+    }
+
+    #[test]
+    fn unterminated_quote_reads_to_the_end() {
+        assert_eq!(table("a,\"b\nc").rows, vec![vec!["a", "b\nc"]]);
+    }
+
+    #[test]
+    fn sorts_mixed_columns_with_numbers_first() {
+        let mut table = table("n\nn/a\n10\nNaN\n9\n-inf\nabc\n2.5\n");
+        assert!(table.sort("n").is_ok());
+        assert_eq!(
+            column(&table, 0),
+            ["-inf", "2.5", "9", "10", "NaN", "abc", "n/a"]
+        );
+
+        assert!(table.sort("-1").is_ok());
+        assert_eq!(
+            column(&table, 0),
+            ["n/a", "abc", "NaN", "10", "9", "2.5", "-inf"]
+        );
+    }
+
+    #[test]
+    fn sorts_by_a_column_that_is_not_shown() {
+        let arguments = json!({"format": "csv", "columns": "name", "sort": "-score"});
+        let table = CsvTable::from_arguments("name,score\nA,1\nB,3\nC,2\n", &arguments)
+            .ok()
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            table.rows,
+            vec![vec!["name"], vec!["B"], vec!["C"], vec!["A"]]
+        );
+    }
+
+    #[test]
+    fn sort_rejects_unknown_columns() {
+        assert!(table("a\n1\n").sort("b").is_err());
+    }
+
+    #[test]
+    fn formats_only_the_given_columns() {
+        let arguments = json!({
+            "format": "csv",
+            "decimals": 1,
+            "number-columns": "score",
+        });
+        let table = CsvTable::from_arguments("year,id,score\n2023,007,3.14159\n", &arguments)
+            .ok()
+            .flatten()
+            .unwrap();
+        assert_eq!(table.rows[1], ["2023", "007", "3.1"]);
+    }
+
+    #[test]
+    fn selects_and_limits_rows() {
+        let arguments = json!({
+            "format": "csv",
+            "columns": "b,1",
+            "sort": "-a",
+            "limit": 1,
+        });
+        let table = CsvTable::from_arguments("a,b\n1,x\n2,y\n", &arguments)
+            .ok()
+            .flatten()
+            .unwrap();
+        assert_eq!(table.rows, vec![vec!["b", "a"], vec!["y", "2"]]);
+    }
+
+    #[test]
+    fn picks_a_delimiter_that_is_not_used() {
+        let (content, delimiter) = table("a|b,c\n").to_table_content();
+        assert_eq!(delimiter, "\t");
+        assert_eq!(content, "a|b\tc");
+    }
+}
@@ -0,0 +1,293 @@
+use std::env;
+
+/// The TeX engine the LaTeX output is written for, chosen using the constant
+/// `engine`. XeLaTeX and LuaLaTeX read Unicode and system fonts using
+/// fontspec, while pdfLaTeX is limited to the fonts of TeX.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Engine {
+    Pdflatex,
+    Xelatex,
+    Lualatex,
+}
+
+/// The fonts used by XeLaTeX and LuaLaTeX unless the constants `main_font`,
+/// `sans_font` and `mono_font` say otherwise. They look like the fonts used
+/// with pdfLaTeX, Latin Modern and Helvetica.
+const DEFAULT_FONTS: [(&str, &str, &str); 3] = [
+    ("main_font", "setmainfont", "Latin Modern Roman"),
+    ("sans_font", "setsansfont", "TeX Gyre Heros"),
+    ("mono_font", "setmonofont", "Latin Modern Mono"),
+];
+
+/// A language that isn't written in the Latin script, and so needs fonts of
+/// its own
+struct ScriptLanguage {
+    /// The name used by babel and polyglossia
+    name: &'static str,
+    /// The font encoding used by pdfLaTeX. Languages without one need XeLaTeX
+    /// or LuaLaTeX.
+    encoding: Option<&'static str>,
+    /// The font used by XeLaTeX and LuaLaTeX, all of which come with TeX Live
+    font: &'static str,
+    /// The script, as named by fontspec
+    script: &'static str,
+    right_to_left: bool,
+}
+
+const SCRIPT_LANGUAGES: [ScriptLanguage; 5] = [
+    ScriptLanguage {
+        name: "russian",
+        encoding: Some("T2A"),
+        font: "CMU Serif",
+        script: "Cyrillic",
+        right_to_left: false,
+    },
+    ScriptLanguage {
+        name: "greek",
+        encoding: Some("LGR"),
+        font: "CMU Serif",
+        script: "Greek",
+        right_to_left: false,
+    },
+    ScriptLanguage {
+        name: "chinese",
+        encoding: None,
+        font: "FandolSong",
+        script: "CJK",
+        right_to_left: false,
+    },
+    ScriptLanguage {
+        name: "arabic",
+        encoding: None,
+        font: "Amiri",
+        script: "Arabic",
+        right_to_left: true,
+    },
+    ScriptLanguage {
+        name: "persian",
+        encoding: None,
+        font: "Amiri",
+        script: "Arabic",
+        right_to_left: true,
+    },
+];
+
+fn script_language(name: &str) -> Option<&'static ScriptLanguage> {
+    SCRIPT_LANGUAGES
+        .iter()
+        .find(|language| language.name == name)
+}
+
+impl Engine {
+    pub(crate) fn from_env() -> Self {
+        let Ok(engine) = env::var("engine") else {
+            return Engine::Pdflatex;
+        };
+
+        match engine.trim().to_lowercase().as_str() {
+            "pdflatex" => Engine::Pdflatex,
+            "xelatex" => Engine::Xelatex,
+            "lualatex" => Engine::Lualatex,
+            _ => {
+                eprintln!("Unknown engine '{engine}'. Use any of pdflatex, xelatex and lualatex.");
+                Engine::Pdflatex
+            }
+        }
+    }
+
+    /// The name of the program, as used by `% !TEX program`
+    pub(crate) fn program(&self) -> &'static str {
+        match self {
+            Engine::Pdflatex => "pdflatex",
+            Engine::Xelatex => "xelatex",
+            Engine::Lualatex => "lualatex",
+        }
+    }
+
+    /// The packages that set up the input encoding and the fonts
+    pub(crate) fn font_imports(&self) -> Vec<String> {
+        if *self == Engine::Pdflatex {
+            for (name, _, _) in DEFAULT_FONTS {
+                if env::var(name).is_ok() {
+                    eprintln!(
+                        "The constant '{name}' is only used with the engines xelatex and lualatex."
+                    );
+                }
+            }
+
+            return vec![
+                "\\usepackage[T1]{fontenc}".to_string(),
+                "\\usepackage[utf8]{inputenc}".to_string(),
+                "\\usepackage{lmodern}".to_string(),
+                "\\usepackage{helvet}".to_string(),
+            ];
+        }
+
+        let mut fontspec = vec!["\\usepackage{fontspec}".to_string()];
+        for (name, command, default) in DEFAULT_FONTS {
+            let font = env::var(name)
+                .ok()
+                .filter(|font| !font.trim().is_empty())
+                .unwrap_or_else(|| default.to_string());
+            fontspec.push(format!("\\{command}{{{}}}", font.trim()));
+        }
+
+        vec![fontspec.join("\n")]
+    }
+
+    /// Whether the engine can typeset a language, given by its babel name.
+    /// pdfLaTeX only has font encodings for some of the other scripts.
+    pub(crate) fn supports_language(&self, language: &str) -> bool {
+        *self != Engine::Pdflatex
+            || script_language(language).is_none_or(|language| language.encoding.is_some())
+    }
+
+    /// Load babel or polyglossia, as chosen using the constant
+    /// `language_package`, with the languages of the document. The last
+    /// language is the main language.
+    pub(crate) fn language_import(&self, languages: &[&str]) -> String {
+        let package = env::var("language_package").unwrap_or_else(|_| "babel".to_string());
+
+        match package.trim().to_lowercase().as_str() {
+            "polyglossia" if *self == Engine::Pdflatex => {
+                eprintln!(
+                    "polyglossia needs the engine xelatex or lualatex, babel is used instead."
+                );
+            }
+            "polyglossia" | "babel" => {}
+            _ => {
+                eprintln!("Unknown language package '{package}'. Use either babel or polyglossia.")
+            }
+        }
+
+        if self.uses_polyglossia() {
+            polyglossia_import(languages)
+        } else {
+            self.babel_import(languages)
+        }
+    }
+
+    /// Whether the languages are set up using polyglossia rather than babel
+    fn uses_polyglossia(&self) -> bool {
+        *self != Engine::Pdflatex
+            && env::var("language_package")
+                .is_ok_and(|package| package.trim().eq_ignore_ascii_case("polyglossia"))
+    }
+
+    /// The start of an `otherlanguage` environment for a language given by
+    /// its babel name
+    pub(crate) fn begin_other_language(&self, language: &str) -> String {
+        if !self.uses_polyglossia() {
+            return format!("\\begin{{otherlanguage}}{{{language}}}");
+        }
+
+        match polyglossia_language(language) {
+            (name, Some(variant)) => {
+                format!("\\begin{{otherlanguage}}[variant={variant}]{{{name}}}")
+            }
+            (name, None) => format!("\\begin{{otherlanguage}}{{{name}}}"),
+        }
+    }
+
+    fn babel_import(&self, languages: &[&str]) -> String {
+        let scripts: Vec<&ScriptLanguage> = languages
+            .iter()
+            .filter_map(|language| script_language(language))
+            .collect();
+        let mut lines = Vec::new();
+        let mut options: Vec<&str> = Vec::new();
+
+        if *self == Engine::Pdflatex {
+            // babel switches to the encoding of a language, T1 stays the default
+            let encodings: Vec<&str> = scripts
+                .iter()
+                .filter_map(|language| language.encoding)
+                .collect();
+            if !encodings.is_empty() {
+                lines.push(format!(
+                    "\\usepackage[{},T1]{{fontenc}}",
+                    encodings.join(",")
+                ));
+            }
+            options.extend(languages);
+        } else {
+            if scripts.iter().any(|language| language.right_to_left) {
+                options.push(match self {
+                    Engine::Lualatex => "bidi=basic",
+                    _ => "bidi=default",
+                });
+            }
+            // languages in other scripts are set up from the locale files of
+            // babel, together with a font that has their script
+            options.extend(
+                languages
+                    .iter()
+                    .filter(|language| script_language(language).is_none()),
+            );
+        }
+
+        lines.push(format!("\\usepackage[{}]{{babel}}", options.join(",")));
+        if *self != Engine::Pdflatex {
+            for language in scripts {
+                lines.push(format!("\\babelprovide[import]{{{}}}", language.name));
+                lines.push(format!(
+                    "\\babelfont[{}]{{rm}}{{{}}}",
+                    language.name, language.font
+                ));
+            }
+        }
+
+        lines.join("\n")
+    }
+}
+
+/// Get the polyglossia name of a language given by its babel name, along
+/// with the variant of the language if it has one
+fn polyglossia_language(language: &str) -> (&str, Option<&'static str>) {
+    match language {
+        "american" => ("english", Some("american")),
+        "british" => ("english", Some("british")),
+        "brazilian" => ("portuguese", Some("brazilian")),
+        "ngerman" => ("german", None),
+        "norsk" => ("norwegian", Some("bokmal")),
+        "nynorsk" => ("norwegian", Some("nynorsk")),
+        _ => (language, None),
+    }
+}
+
+/// Load polyglossia with the languages given by their babel names. The
+/// variants of the other languages are chosen by each `otherlanguage`
+/// environment, see [Engine::begin_other_language].
+fn polyglossia_import(languages: &[&str]) -> String {
+    let (main, others) = languages.split_last().unwrap();
+    let (main, variant) = polyglossia_language(main);
+    let variant = variant
+        .map(|variant| format!("[variant={variant}]"))
+        .unwrap_or_default();
+    let mut lines = vec![
+        "\\usepackage{polyglossia}".to_string(),
+        format!("\\setdefaultlanguage{variant}{{{main}}}"),
+    ];
+
+    // a language is loaded once, whatever variants of it are used
+    let mut names: Vec<&str> = Vec::new();
+    for (name, _) in others.iter().map(|language| polyglossia_language(language)) {
+        if name != main && !names.contains(&name) {
+            names.push(name);
+        }
+    }
+    if !names.is_empty() {
+        lines.push(format!("\\setotherlanguages{{{}}}", names.join(",")));
+    }
+    for language in languages
+        .iter()
+        .filter_map(|language| script_language(language))
+    {
+        lines.push(format!(
+            "\\newfontfamily\\{}font[Script={}]{{{}}}",
+            language.name, language.script, language.font
+        ));
+    }
+
+    lines.join("\n")
+}
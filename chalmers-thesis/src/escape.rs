@@ -0,0 +1,20 @@
+/// Escape text for HTML and SVG, both in content and in attribute values
+pub(crate) fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Escape the characters that have a special meaning in LaTeX text
+pub(crate) fn escape_latex(text: &str) -> String {
+    text.chars()
+        .map(|c| match c {
+            '\\' => r"\textbackslash{}".to_string(),
+            '{' | '}' | '#' | '$' | '%' | '&' | '_' => format!(r"\{c}"),
+            '~' => r"\textasciitilde{}".to_string(),
+            '^' => r"\textasciicircum{}".to_string(),
+            c => c.to_string(),
+        })
+        .collect()
+}
@@ -0,0 +1,652 @@
+use crate::{escape::escape_html, svg::include_svg, Error, ModuleInvocation};
+use serde_json::{json, Map, Value};
+use std::env;
+
+/// Arguments that are handled by the float itself and thus never forwarded
+/// to the wrapped module
+const FLOAT_ARGUMENTS: [&str; 7] = [
+    "kind",
+    "caption",
+    "label",
+    "args",
+    "placement",
+    "full-width",
+    "landscape",
+];
+
+/// A kind of numbered float, such as a figure or a table, together with the
+/// module that renders its content.
+#[derive(Clone)]
+pub(crate) struct FloatKind {
+    /// The name used to refer to the kind, e.g. "listing"
+    pub(crate) name: String,
+    /// The name shown in front of the number, e.g. "Listing"
+    pub(crate) display_name: String,
+    /// The module that is wrapped by the float
+    pub(crate) module: String,
+    /// The entry pushed to `structure`, floats sharing a counter share numbering
+    pub(crate) counter: String,
+    /// The LaTeX environment used for the float
+    pub(crate) environment: String,
+    /// Whether the wrapped module takes `caption` and `label` arguments and
+    /// renders the float itself (like std `[image]` and `[table]` do)
+    pub(crate) native_caption: bool,
+}
+
+impl FloatKind {
+    fn builtin(
+        name: &str,
+        display_name: &str,
+        module: &str,
+        counter: &str,
+        environment: &str,
+        native_caption: bool,
+    ) -> Self {
+        Self {
+            name: name.to_string(),
+            display_name: display_name.to_string(),
+            module: module.to_string(),
+            counter: counter.to_string(),
+            environment: environment.to_string(),
+            native_caption,
+        }
+    }
+
+    /// The float kinds that are available without declaring them
+    pub(crate) fn builtins() -> Vec<Self> {
+        vec![
+            Self::builtin("figure", "Figure", "image", "fig", "figure", true),
+            Self::builtin("table", "Table", "table", "tab", "table", true),
+            Self::builtin("big-table", "Table", "big-table", "tab", "table", true),
+            Self::builtin(
+                "listing",
+                "Listing",
+                "code",
+                "float/listing",
+                "listing",
+                false,
+            ),
+            Self::builtin("chart", "Figure", "chart", "fig", "figure", false),
+            Self::builtin(
+                "syntax-tree",
+                "Figure",
+                "syntax-tree",
+                "fig",
+                "figure",
+                false,
+            ),
+            Self::builtin("gantt", "Figure", "gantt", "fig", "figure", false),
+        ]
+    }
+
+    /// Get all float kinds, both the built in ones and the ones declared
+    /// using `[float-kind]`
+    pub(crate) fn all() -> Vec<Self> {
+        let var = env::var("float-kinds").unwrap_or_else(|_| "[]".to_string());
+        let declared: Vec<String> = serde_json::from_str(&var).unwrap();
+
+        let mut kinds = Self::builtins();
+        for entry in declared {
+            let entry: Value = serde_json::from_str(&entry).unwrap();
+            let get = |key: &str| entry[key].as_str().unwrap_or_default().to_string();
+
+            let name = get("name");
+            let numbered_as = get("numbered-as");
+            // a kind that is numbered as another kind also shares its environment
+            // and name, unless another name is given
+            let kind = match kinds.iter().find(|kind| kind.name == numbered_as) {
+                Some(other) => Self {
+                    name: name.clone(),
+                    module: get("module"),
+                    native_caption: get("native-caption") == "true",
+                    ..other.clone()
+                },
+                None => Self {
+                    name: name.clone(),
+                    display_name: capitalize(&name),
+                    module: get("module"),
+                    counter: format!("float/{name}"),
+                    environment: name.clone(),
+                    native_caption: get("native-caption") == "true",
+                },
+            };
+
+            let display_name = get("display-name");
+            let kind = if display_name.is_empty() {
+                kind
+            } else {
+                Self {
+                    display_name,
+                    ..kind
+                }
+            };
+
+            kinds.retain(|other| other.name != kind.name);
+            kinds.push(kind);
+        }
+
+        kinds
+    }
+
+    /// Find a float kind by name
+    pub(crate) fn find(name: &str) -> Option<Self> {
+        Self::all().into_iter().find(|kind| kind.name == name)
+    }
+
+    /// Get the kind that owns a counter, i.e the kind that other kinds are numbered as
+    pub(crate) fn from_counter(counter: &str) -> Option<Self> {
+        Self::all().into_iter().find(|kind| kind.counter == counter)
+    }
+
+    /// Environments defined by LaTeX itself, all other ones are declared using
+    /// `\newfloat` from the float package
+    fn needs_declaration(&self) -> bool {
+        !matches!(self.environment.as_str(), "figure" | "table")
+    }
+
+    /// The LaTeX command listing all floats of this kind
+    fn latex_list_command(&self) -> String {
+        match self.environment.as_str() {
+            "figure" => r"\listoffigures".to_string(),
+            "table" => r"\listoftables".to_string(),
+            env => format!(r"\listof{{{env}}}{{List of {}s}}", self.display_name),
+        }
+    }
+}
+
+/// Placement and size of a float on the page
+pub(crate) struct Layout {
+    /// One of "here", "top", "bottom", "page" and "auto"
+    placement: String,
+    /// Whether the float may extend into the margins
+    full_width: bool,
+    /// Whether the float is rotated onto a landscape page
+    landscape: bool,
+}
+
+impl Layout {
+    /// Read the layout of a float. A full width float is always placed where
+    /// it is written, since that is what lets it follow the margins of
+    /// `adjustwidth`, so it can't be given another placement.
+    pub(crate) fn from_arguments(arguments: &Value) -> Result<Self, Error> {
+        let layout = Self {
+            placement: arguments["placement"]
+                .as_str()
+                .unwrap_or("here")
+                .to_string(),
+            full_width: arguments["full-width"].as_str() == Some("true"),
+            landscape: arguments["landscape"].as_str() == Some("true"),
+        };
+
+        if layout.full_width && layout.placement != "here" {
+            return Err(Error::Arguments(format!(
+                "A full width float is placed where it is written, so it can't have the placement '{}'.",
+                layout.placement
+            )));
+        }
+        Ok(layout)
+    }
+
+    /// The classes of the HTML wrapper, see `template.css`. A web page has no
+    /// top or bottom to place a float at, so only a float on a page of its own
+    /// is kept, which applies when the page is printed.
+    fn html_classes(&self) -> Vec<&str> {
+        let mut classes = Vec::new();
+        if self.placement == "page" {
+            classes.push("placement-page");
+        }
+        if self.full_width {
+            classes.push("full-width");
+        }
+        if self.landscape {
+            classes.push("landscape");
+        }
+        classes
+    }
+
+    /// The float specifier used in LaTeX
+    fn latex_placement(&self) -> &str {
+        match self.placement.as_str() {
+            "top" => "t",
+            "bottom" => "b",
+            "page" => "p",
+            "auto" => "tbp",
+            _ => "H",
+        }
+    }
+
+    /// The output that goes before the float
+    pub(crate) fn open(&self, to: &str) -> Vec<Value> {
+        let mut result = Vec::new();
+
+        match to {
+            "html" if !self.html_classes().is_empty() => {
+                result.push(json!(format!(
+                    r#"<div class="float-layout {}">"#,
+                    self.html_classes().join(" ")
+                )));
+            }
+            "latex" => {
+                if self.landscape {
+                    result.push(json!("\\begin{landscape}\n"));
+                }
+                if self.full_width {
+                    result.push(json!("\\begin{adjustwidth}{-2cm}{-2cm}\n"));
+                }
+            }
+            _ => {}
+        }
+
+        result
+    }
+
+    /// The output that goes after the float, closing what [Layout::open] opened
+    pub(crate) fn close(&self, to: &str) -> Vec<Value> {
+        let mut result = Vec::new();
+
+        match to {
+            "html" if !self.html_classes().is_empty() => result.push(json!("</div>")),
+            "latex" => {
+                if self.full_width {
+                    result.push(json!("\\end{adjustwidth}\n"));
+                }
+                if self.landscape {
+                    result.push(json!("\\end{landscape}\n"));
+                }
+            }
+            _ => {}
+        }
+
+        result
+    }
+}
+
+fn capitalize(s: &str) -> String {
+    let mut chars = s.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().collect::<String>() + chars.as_str(),
+        None => String::new(),
+    }
+}
+
+/// Get all floats that have been pushed to the `floats` list
+pub(crate) fn get_floats() -> Vec<Value> {
+    let var = env::var("floats").unwrap_or_else(|_| "[]".to_string());
+    let array: Vec<String> = serde_json::from_str(&var).unwrap();
+
+    array
+        .iter()
+        .map(|float| serde_json::from_str(float).unwrap())
+        .collect()
+}
+
+/// Get the label of a float. element-number needs a label to find the float,
+/// so one is made up if none is given.
+pub(crate) fn float_label(kind: &FloatKind, arguments: &Value) -> String {
+    match arguments["label"].as_str().unwrap_or_default() {
+        "" => format!("float/{}/{}", kind.name, rand::random::<u64>()),
+        label => label.to_string(),
+    }
+}
+
+/// Push a float to `structure`, so that it is numbered, and to `floats`, so
+/// that it shows up in lists of floats
+pub(crate) fn register_float(kind: &FloatKind, label: &str, caption: &str) -> Vec<Value> {
+    let payload = json!({
+        "kind": kind.name,
+        "counter": kind.counter,
+        "label": label,
+        "caption": caption,
+    });
+
+    vec![
+        json!({"name": "list-push", "arguments": {"name": "structure"}, "data": kind.counter}),
+        json!({"name": "list-push", "arguments": {"name": "structure"}, "data": format!("label/{label}")}),
+        json!({"name": "list-push", "arguments": {"name": "floats"}, "data": serde_json::to_string(&payload).unwrap()}),
+    ]
+}
+
+/// Transform `[float-kind]`, declaring a new kind of float
+pub(crate) fn transform_float_kind(input: Value, _to: &str) -> Result<String, Error> {
+    let payload = json!({
+        "name": input["data"].as_str().unwrap().trim(),
+        "module": input["arguments"]["module"],
+        "display-name": input["arguments"]["display-name"],
+        "numbered-as": input["arguments"]["numbered-as"],
+        "native-caption": input["arguments"]["native-caption"],
+    });
+
+    let json = json!([
+        {"name": "list-push", "arguments": {"name": "float-kinds"}, "data": serde_json::to_string(&payload).unwrap()},
+    ]);
+
+    Ok(serde_json::to_string(&json).unwrap())
+}
+
+/// Transform `[float]`, where the kind is given as an argument
+pub(crate) fn transform_float(input: Value, to: &str) -> Result<String, Error> {
+    let name = input["arguments"]["kind"].as_str().unwrap();
+    let Some(kind) = FloatKind::find(name) else {
+        return Err(Error::UnknownFloatKind(name.to_string()));
+    };
+
+    transform_float_of(&kind, input, to)
+}
+
+/// Wrap the module of the given float kind, adding a numbered caption and a
+/// label. Arguments that aren't handled by the float are passed on to the
+/// wrapped module.
+pub(crate) fn transform_float_of(
+    kind: &FloatKind,
+    input: Value,
+    to: &str,
+) -> Result<String, Error> {
+    let data = input["data"].as_str().unwrap();
+    let empty = Map::new();
+    let arguments = input["arguments"].as_object().unwrap_or(&empty);
+
+    let caption = input["arguments"]["caption"].as_str().unwrap_or_default();
+    let label = float_label(kind, &input["arguments"]);
+
+    let mut module_invoc = ModuleInvocation::new(&kind.module);
+    for (name, value) in arguments {
+        if FLOAT_ARGUMENTS.contains(&name.as_str()) {
+            continue;
+        }
+        module_invoc = match value {
+            Value::String(s) => module_invoc.arg(name, s),
+            other => module_invoc.arg(name, other),
+        };
+    }
+    if let Some(args) = input["arguments"]["args"].as_str() {
+        module_invoc = module_invoc
+            .args(args)
+            .map_err(|error| Error::Arguments(format!("Invalid args '{args}': {error}")))?;
+    }
+    let module_invoc = module_invoc.body(data);
+
+    let numbered_caption = format!(
+        "**{} [element-number]({label}):** {caption}",
+        kind.display_name
+    );
+
+    let mut result = register_float(kind, &label, caption);
+
+    // std [image] includes SVG images using the svg package, which needs
+    // Inkscape and shell escape, so they are drawn using PGF instead
+    let svg_image = kind.module == "image" && to == "latex" && data.trim().ends_with(".svg");
+    let native_caption = kind.native_caption && !svg_image;
+
+    let layout = Layout::from_arguments(&input["arguments"])?;
+    // modules that render the float themselves, like std [image] and [table],
+    // place it where it is written
+    if native_caption && to == "latex" && layout.placement != "here" {
+        eprintln!(
+            "The placement '{}' is ignored, since [{}] places the {} where it is written.",
+            layout.placement, kind.module, kind.name
+        );
+    }
+    result.append(&mut layout.open(to));
+
+    if native_caption {
+        // making use of the fact that caption becomes inline-content in the wrapped module
+        let caption = if to == "html" {
+            numbered_caption
+        } else {
+            caption.to_string()
+        };
+
+        let module_invoc = module_invoc.arg("caption", caption).arg("label", &label);
+        result.push(json!({"name": "block_content", "data": module_invoc.to_string()}));
+    } else {
+        let module_invoc = module_invoc.to_string();
+
+        match to {
+            "html" => {
+                result.push(json!(format!(
+                    r#"<figure class="float float-{}" id="{}">"#,
+                    escape_html(&kind.name),
+                    escape_html(&label)
+                )));
+                result.push(json!({"name": "block_content", "data": module_invoc}));
+                result.push(json!("<figcaption>"));
+                result.push(json!({"name": "inline_content", "data": numbered_caption}));
+                result.push(json!("</figcaption></figure>"));
+            }
+            "latex" => {
+                if kind.needs_declaration() {
+                    result.push(json!({"name": "set-add", "arguments": {"name": "imports"}, "data": format!(
+                        "\\usepackage{{float}}\n\\floatstyle{{plain}}\n\\newfloat{{{env}}}{{tbp}}{{lo{env}}}[chapter]\n\\floatname{{{env}}}{{{name}}}",
+                        env = kind.environment,
+                        name = kind.display_name,
+                    )}));
+                }
+                result.push(json!(format!(
+                    "\\begin{{{}}}[{}]\n\\centering\n",
+                    kind.environment,
+                    layout.latex_placement()
+                )));
+                if svg_image {
+                    let width = arguments
+                        .get("width")
+                        .and_then(Value::as_f64)
+                        .unwrap_or(1.0);
+                    result.push(json!(format!(
+                        "{}\n",
+                        include_svg(data.trim(), &format!("{width}\\linewidth"))?
+                    )));
+                } else {
+                    result.push(json!({"name": "block_content", "data": module_invoc}));
+                }
+                result.push(json!(r"\caption{"));
+                result.push(json!({"name": "inline_content", "data": caption}));
+                result.push(json!(format!(
+                    "}}\n\\label{{{}}}\n\\end{{{}}}\n",
+                    label.replace('"', "%22"),
+                    kind.environment
+                )));
+            }
+            other => {
+                eprintln!("Cannot convert float to {other}");
+                result.push(json!({"name": "block_content", "data": module_invoc}));
+                result.push(json!({"name": "inline_content", "data": numbered_caption}));
+            }
+        }
+    }
+
+    result.append(&mut layout.close(to));
+
+    Ok(serde_json::to_string(&result).unwrap())
+}
+
+/// Transform `[figure-group]`, a figure made up of several subfigures that
+/// share one caption. Each line of the content is one subfigure, written as
+/// `path | caption | label` where caption and label are optional.
+pub(crate) fn transform_figure_group(input: Value, to: &str) -> Result<String, Error> {
+    let data = input["data"].as_str().unwrap();
+    let kind = FloatKind::find("figure").unwrap();
+    let caption = input["arguments"]["caption"].as_str().unwrap_or_default();
+    let label = float_label(&kind, &input["arguments"]);
+    let delimiter = input["arguments"]["delimiter"].as_str().unwrap_or("|");
+    let columns = input["arguments"]["columns"].as_u64().unwrap_or(2).max(1);
+
+    let subfigures = data
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            let mut parts = line.split(delimiter).map(str::trim);
+            let path = parts.next().unwrap_or_default().to_string();
+            let caption = parts.next().unwrap_or_default().to_string();
+            let label = match parts.next() {
+                Some(label) if !label.is_empty() => label.to_string(),
+                _ => format!("float/subfigure/{}", rand::random::<u64>()),
+            };
+            (path, caption, label)
+        })
+        .collect::<Vec<_>>();
+    // subfigures are lettered from a to z, like \alph does in LaTeX
+    if subfigures.len() > 26 {
+        return Err(Error::FigureGroup(format!(
+            "there are {} subfigures, but at most 26 can be lettered",
+            subfigures.len()
+        )));
+    }
+
+    let mut result = register_float(&kind, &label, caption);
+    for (_, _, sub_label) in &subfigures {
+        result.push(
+            json!({"name": "list-push", "arguments": {"name": "structure"}, "data": "subfig"}),
+        );
+        result.push(json!({"name": "list-push", "arguments": {"name": "structure"}, "data": format!("label/{sub_label}")}));
+    }
+
+    let layout = Layout::from_arguments(&input["arguments"])?;
+    result.append(&mut layout.open(to));
+
+    match to {
+        "html" => {
+            result.push(json!(format!(
+                r#"<figure class="float figure-group" id="{}" style="grid-template-columns: repeat({columns}, 1fr)">"#,
+                escape_html(&label)
+            )));
+
+            for (index, (path, sub_caption, sub_label)) in subfigures.iter().enumerate() {
+                result.push(json!(format!(
+                    r#"<figure id="{}"><img src="{}" alt="{}"/><figcaption>({}) "#,
+                    escape_html(sub_label),
+                    escape_html(path),
+                    escape_html(sub_caption),
+                    crate::subfigure_letter(index + 1),
+                )));
+                result.push(json!({"name": "inline_content", "data": sub_caption}));
+                result.push(json!("</figcaption></figure>"));
+            }
+
+            result.push(json!("<figcaption>"));
+            result.push(json!({"name": "inline_content", "data": format!("**Figure [element-number]({label}):** {caption}")}));
+            result.push(json!("</figcaption></figure>"));
+        }
+        "latex" => {
+            result.push(json!({"name": "set-add", "arguments": {"name": "imports"}, "data":
+                "\\usepackage{subcaption}\n\\captionsetup[sub]{labelformat=parens}\n\\renewcommand{\\thesubfigure}{\\alph{subfigure}}"
+            }));
+            result.push(json!(format!(
+                "\\begin{{figure}}[{}]\n\\centering\n",
+                layout.latex_placement()
+            )));
+
+            // leave some room between the subfigures on each row
+            let width = 1.0 / columns as f64 - 0.02;
+            for (index, (path, sub_caption, sub_label)) in subfigures.iter().enumerate() {
+                let include = if path.ends_with(".svg") {
+                    include_svg(path, "\\textwidth")?
+                } else {
+                    format!(r"\includegraphics[width=\textwidth]{{{path}}}")
+                };
+                result.push(json!(format!(
+                    "\\begin{{subfigure}}[b]{{{width:.2}\\textwidth}}\n\\centering\n{include}\n\\caption{{"
+                )));
+                result.push(json!({"name": "inline_content", "data": sub_caption}));
+
+                // break the row after the last subfigure of it
+                let separator = if (index as u64 + 1).is_multiple_of(columns) {
+                    "\\par\\medskip"
+                } else {
+                    "\\hfill"
+                };
+                result.push(json!(format!(
+                    "}}\n\\label{{{}}}\n\\end{{subfigure}}{separator}\n",
+                    sub_label.replace('"', "%22")
+                )));
+            }
+
+            result.push(json!(r"\caption{"));
+            result.push(json!({"name": "inline_content", "data": caption}));
+            result.push(json!(format!(
+                "}}\n\\label{{{}}}\n\\end{{figure}}\n",
+                label.replace('"', "%22")
+            )));
+        }
+        other => eprintln!("Cannot convert figure-group to {other}"),
+    }
+
+    result.append(&mut layout.close(to));
+
+    Ok(serde_json::to_string(&result).unwrap())
+}
+
+/// Transform `[list-of]`, listing all floats of a given kind
+pub(crate) fn transform_list_of(input: Value, to: &str) -> Result<String, Error> {
+    let name = input["data"].as_str().unwrap().trim();
+    let Some(kind) = FloatKind::find(name) else {
+        return Err(Error::UnknownFloatKind(name.to_string()));
+    };
+
+    let json = match to {
+        "latex" => json!([kind.latex_list_command()]),
+        "html" => {
+            let owner = FloatKind::from_counter(&kind.counter).unwrap_or(kind);
+            let mut result = vec![json!(format!(
+                r#"<div class="list-of"><h2>List of {}s</h2><ul>"#,
+                owner.display_name
+            ))];
+
+            for float in get_floats()
+                .iter()
+                .filter(|float| float["counter"] == owner.counter.as_str())
+            {
+                let label = float["label"].as_str().unwrap();
+                let escaped_label = label.replace('"', "%22");
+                let caption = float["caption"].as_str().unwrap();
+
+                result.push(json!(format!(
+                    r##"<li><a href="#{escaped_label}">{} "##,
+                    owner.display_name
+                )));
+                result.push(
+                    json!({"name": "inline_content", "data": format!("[element-number]({label})")}),
+                );
+                result.push(json!("</a>: "));
+                result.push(json!({"name": "inline_content", "data": caption}));
+                result.push(json!("</li>"));
+            }
+
+            result.push(json!("</ul></div>"));
+            Value::Array(result)
+        }
+        _ => json!([]),
+    };
+
+    Ok(serde_json::to_string(&json).unwrap())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn draws_svg_figures_using_pgf() {
+        let path = env::temp_dir().join("chalmers-thesis-float-test.svg");
+        std::fs::write(
+            &path,
+            r#"<svg width="10" height="10"><rect width="10" height="10"/></svg>"#,
+        )
+        .unwrap();
+
+        let kind = FloatKind::find("figure").unwrap();
+        let input = json!({
+            "data": path.to_str().unwrap(),
+            "arguments": {"caption": "Overview", "label": "fig:overview", "width": 0.5},
+        });
+        let latex = transform_float_of(&kind, input, "latex").ok().unwrap();
+        let output: Vec<Value> = serde_json::from_str(&latex).unwrap();
+        let strings: Vec<&str> = output.iter().filter_map(Value::as_str).collect();
+
+        assert!(strings.iter().any(|s| s.starts_with("\\begin{figure}[H]")));
+        assert!(strings
+            .iter()
+            .any(|s| s.starts_with("\\resizebox{0.5\\linewidth}{!}{")));
+        assert!(!output.iter().any(|value| value["name"] == "block_content"));
+        assert!(strings.iter().any(|s| s.contains("\\label{fig:overview}")));
+    }
+}
@@ -0,0 +1,105 @@
+/// A page of the front matter. Pages without content, such as a preface
+/// when the constant `preface` isn't declared, are left out.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Page {
+    Cover,
+    Title,
+    Imprint,
+    /// How to cite the thesis, only in HTML
+    HowToCite,
+    Dedication,
+    Epigraph,
+    /// The abstract, sammandrag and any abstracts added using `[abstract]`
+    Abstract,
+    Preface,
+    Acknowledgements,
+    Contributions,
+    /// The table of contents, only in LaTeX
+    Contents,
+    Abbreviations,
+}
+
+/// The order of the pages unless the constant `front_matter` says otherwise
+const DEFAULT_ORDER: [Page; 12] = [
+    Page::Cover,
+    Page::Title,
+    Page::Imprint,
+    Page::HowToCite,
+    Page::Dedication,
+    Page::Epigraph,
+    Page::Abstract,
+    Page::Preface,
+    Page::Acknowledgements,
+    Page::Contributions,
+    Page::Contents,
+    Page::Abbreviations,
+];
+
+impl Page {
+    fn parse(name: &str) -> Option<Self> {
+        let page = match name {
+            "cover" => Page::Cover,
+            "title" => Page::Title,
+            "imprint" => Page::Imprint,
+            "how-to-cite" => Page::HowToCite,
+            "dedication" => Page::Dedication,
+            "epigraph" => Page::Epigraph,
+            "abstract" => Page::Abstract,
+            "preface" => Page::Preface,
+            "acknowledgements" => Page::Acknowledgements,
+            "contributions" => Page::Contributions,
+            "contents" => Page::Contents,
+            "abbreviations" => Page::Abbreviations,
+            _ => return None,
+        };
+
+        Some(page)
+    }
+
+    /// Get the pages to include, in order, from a comma separated list such as
+    /// "abstract, contents". Pages that aren't listed are left out.
+    pub(crate) fn from_list(list: Option<&str>) -> Vec<Self> {
+        let Some(list) = list else {
+            return DEFAULT_ORDER.to_vec();
+        };
+
+        let mut pages = Vec::new();
+        for name in list
+            .split(',')
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+        {
+            match Self::parse(name) {
+                Some(page) if !pages.contains(&page) => pages.push(page),
+                Some(_) => eprintln!("The front matter page '{name}' is listed twice."),
+                None => eprintln!(
+                    "Unknown front matter page '{name}'. Use any of cover, title, imprint, how-to-cite, dedication, epigraph, abstract, preface, acknowledgements, contributions, contents and abbreviations."
+                ),
+            }
+        }
+
+        pages
+    }
+}
+
+/// Read a list of abbreviations written one per line as `ABBR: expansion`
+/// (or `ABBR | expansion`)
+pub(crate) fn parse_abbreviations(text: &str) -> Vec<(String, String)> {
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(|line| {
+            let split = line.split_once('|').or_else(|| line.split_once(':'));
+            match split {
+                Some((abbreviation, expansion)) => (
+                    abbreviation.trim().to_string(),
+                    expansion.trim().to_string(),
+                ),
+                None => {
+                    eprintln!("The abbreviation '{line}' is missing an expansion.");
+                    (line.to_string(), String::new())
+                }
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,534 @@
+use crate::{
+    chart::{FONT, PALETTE},
+    escape::{escape_html, escape_latex},
+    Error,
+};
+use serde_json::{json, Value};
+use std::{fmt::Write, fs};
+
+/// Approximate width of a character, used to size the column of task names in HTML
+const CHAR_WIDTH: f64 = 7.0;
+const ROW_HEIGHT: f64 = 28.0;
+const HEADER_HEIGHT: f64 = 28.0;
+const LEGEND_HEIGHT: f64 = 32.0;
+const PADDING: f64 = 12.0;
+
+/// The colour of tasks without any owner
+const NO_OWNER: &str = "#7f7f7f";
+
+/// A row of the chart, either a task spanning one or more weeks or a
+/// milestone at a single week
+enum Row {
+    Task {
+        name: String,
+        start: i64,
+        end: i64,
+        owners: Vec<String>,
+    },
+    Milestone {
+        name: String,
+        week: i64,
+    },
+}
+
+struct Gantt {
+    rows: Vec<Row>,
+    /// Every owner, in the order they first occur
+    owners: Vec<String>,
+    first_week: i64,
+    last_week: i64,
+    week_label: String,
+}
+
+/// Transform `[gantt]`, which draws a time plan from one task per line,
+/// written as `name | start-end | owners`, and milestones written as
+/// `milestone: name | week`.
+pub(crate) fn transform_gantt(input: Value, to: &str) -> Result<String, Error> {
+    let data = input["data"].as_str().unwrap_or_default();
+    let arguments = &input["arguments"];
+
+    // reading the plan from a file lets the planning report and the thesis share it
+    let file = arguments["file"].as_str().unwrap_or_default().trim();
+    let text = if file.is_empty() {
+        data.to_string()
+    } else {
+        fs::read_to_string(file)
+            .map_err(|error| Error::Gantt(format!("Could not read '{file}': {error}")))?
+    };
+
+    let gantt = Gantt::parse(&text, arguments)?;
+
+    let json = match to {
+        "html" => json!([gantt.to_svg(arguments["width"].as_f64().unwrap_or(640.0))]),
+        "latex" => json!([
+            {"name": "set-add", "arguments": {"name": "imports"}, "data": "\\usepackage{tikz}\n\\usetikzlibrary{shapes.geometric}"},
+            gantt.to_tikz(),
+        ]),
+        other => {
+            eprintln!("Cannot convert gantt to {other}");
+            json!([])
+        }
+    };
+
+    Ok(serde_json::to_string(&json).unwrap())
+}
+
+/// Parse a week range such as `3-5`, or a single week such as `4`
+fn parse_weeks(weeks: &str, name: &str) -> Result<(i64, i64), Error> {
+    let parse = |week: &str| {
+        week.trim()
+            .parse::<i64>()
+            .map_err(|_| Error::Gantt(format!("Invalid week '{}' for '{name}'", week.trim())))
+    };
+
+    let (start, end) = match weeks.split_once('-') {
+        Some((start, end)) => (parse(start)?, parse(end)?),
+        None => {
+            let week = parse(weeks)?;
+            (week, week)
+        }
+    };
+
+    if end < start {
+        return Err(Error::Gantt(format!(
+            "'{name}' ends in week {end}, before it starts in week {start}"
+        )));
+    }
+
+    Ok((start, end))
+}
+
+impl Gantt {
+    fn parse(text: &str, arguments: &Value) -> Result<Self, Error> {
+        let mut rows = Vec::new();
+        let mut owners: Vec<String> = Vec::new();
+
+        for line in text.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            if let Some(milestone) = line.strip_prefix("milestone:") {
+                let Some((name, week)) = milestone.split_once('|') else {
+                    return Err(Error::Gantt(format!(
+                        "The milestone '{}' is missing a week",
+                        milestone.trim()
+                    )));
+                };
+                let name = name.trim().to_string();
+                let (week, _) = parse_weeks(week, &name)?;
+                rows.push(Row::Milestone { name, week });
+                continue;
+            }
+
+            let mut fields = line.split('|').map(str::trim);
+            let name = fields.next().unwrap_or_default().to_string();
+            let Some(weeks) = fields.next() else {
+                return Err(Error::Gantt(format!("The task '{name}' is missing weeks")));
+            };
+            let (start, end) = parse_weeks(weeks, &name)?;
+
+            let task_owners: Vec<String> = fields
+                .next()
+                .unwrap_or_default()
+                .split(',')
+                .map(str::trim)
+                .filter(|owner| !owner.is_empty())
+                .map(str::to_string)
+                .collect();
+            for owner in &task_owners {
+                if !owners.contains(owner) {
+                    owners.push(owner.clone());
+                }
+            }
+
+            rows.push(Row::Task {
+                name,
+                start,
+                end,
+                owners: task_owners,
+            });
+        }
+
+        let weeks = rows.iter().flat_map(|row| match row {
+            Row::Task { start, end, .. } => [*start, *end],
+            Row::Milestone { week, .. } => [*week, *week],
+        });
+        let (Some(first_week), Some(last_week)) = (weeks.clone().min(), weeks.max()) else {
+            return Err(Error::Gantt("The chart has no tasks".to_string()));
+        };
+
+        Ok(Self {
+            rows,
+            owners,
+            first_week,
+            last_week,
+            week_label: arguments["week-label"]
+                .as_str()
+                .unwrap_or("Week")
+                .to_string(),
+        })
+    }
+
+    fn weeks(&self) -> i64 {
+        self.last_week - self.first_week + 1
+    }
+
+    fn owner_colour(&self, owner: &str) -> &'static str {
+        let index = self.owners.iter().position(|o| o == owner).unwrap_or(0);
+        PALETTE[index % PALETTE.len()]
+    }
+
+    fn to_svg(&self, width: f64) -> String {
+        let label_width = self
+            .rows
+            .iter()
+            .map(|row| match row {
+                Row::Task { name, .. } | Row::Milestone { name, .. } => name.chars().count(),
+            })
+            .chain([self.week_label.chars().count()])
+            .max()
+            .unwrap_or(0) as f64
+            * CHAR_WIDTH
+            + PADDING;
+        // leave at least half of the width to the weeks
+        let label_width = label_width.min(width / 2.0);
+
+        let legend_height = if self.owners.is_empty() {
+            0.0
+        } else {
+            LEGEND_HEIGHT
+        };
+        let height = HEADER_HEIGHT + self.rows.len() as f64 * ROW_HEIGHT + legend_height + PADDING;
+
+        let plot_left = label_width + PADDING;
+        let plot_right = width - PADDING;
+        let week_width = (plot_right - plot_left) / self.weeks() as f64;
+        let x = |week: i64| plot_left + (week - self.first_week) as f64 * week_width;
+        let row_top = |index: usize| HEADER_HEIGHT + index as f64 * ROW_HEIGHT;
+        let plot_bottom = row_top(self.rows.len());
+
+        let mut svg = String::new();
+        write!(
+            svg,
+            r#"<svg class="gantt" xmlns="http://www.w3.org/2000/svg" viewBox="0 0 {width} {height}" width="{width}" height="{height}" font-family="{FONT}" font-size="12" role="img">"#
+        )
+        .unwrap();
+        svg.push_str(r##"<rect width="100%" height="100%" fill="#ffffff"/>"##);
+
+        // the weeks, labelling fewer of them if they are narrow
+        let step = (24.0 / week_width).ceil().max(1.0) as i64;
+        write!(
+            svg,
+            r#"<text x="{label_width:.1}" y="{:.1}" text-anchor="end" font-weight="bold">{}</text>"#,
+            HEADER_HEIGHT - 10.0,
+            escape_html(&self.week_label)
+        )
+        .unwrap();
+        for week in self.first_week..=self.last_week + 1 {
+            write!(
+                svg,
+                r##"<line x1="{x:.1}" y1="{HEADER_HEIGHT}" x2="{x:.1}" y2="{plot_bottom}" stroke="#e4e4e4"/>"##,
+                x = x(week)
+            )
+            .unwrap();
+            if week <= self.last_week && (week - self.first_week) % step == 0 {
+                write!(
+                    svg,
+                    r#"<text x="{:.1}" y="{:.1}" text-anchor="middle">{week}</text>"#,
+                    x(week) + week_width / 2.0,
+                    HEADER_HEIGHT - 10.0
+                )
+                .unwrap();
+            }
+        }
+
+        for (index, row) in self.rows.iter().enumerate() {
+            let top = row_top(index);
+            let (Row::Task { name, .. } | Row::Milestone { name, .. }) = row;
+            write!(
+                svg,
+                r#"<text x="{label_width:.1}" y="{:.1}" text-anchor="end">{}</text>"#,
+                top + ROW_HEIGHT / 2.0 + 4.0,
+                escape_html(name)
+            )
+            .unwrap();
+
+            match row {
+                Row::Task {
+                    name,
+                    start,
+                    end,
+                    owners,
+                } => {
+                    // the bar is split into one stripe per owner
+                    let colours: Vec<&str> = if owners.is_empty() {
+                        vec![NO_OWNER]
+                    } else {
+                        owners
+                            .iter()
+                            .map(|owner| self.owner_colour(owner))
+                            .collect()
+                    };
+                    let bar_height = ROW_HEIGHT - 10.0;
+                    let stripe = bar_height / colours.len() as f64;
+                    let weeks = if start == end {
+                        format!("week {start}")
+                    } else {
+                        format!("weeks {start}–{end}")
+                    };
+                    write!(svg, "<g><title>{}: {weeks}</title>", escape_html(name)).unwrap();
+                    for (stripe_index, colour) in colours.iter().enumerate() {
+                        write!(
+                            svg,
+                            r#"<rect x="{:.1}" y="{:.1}" width="{:.1}" height="{stripe:.1}" fill="{colour}"/>"#,
+                            x(*start),
+                            top + 5.0 + stripe_index as f64 * stripe,
+                            x(*end + 1) - x(*start)
+                        )
+                        .unwrap();
+                    }
+                    svg.push_str("</g>");
+                }
+                Row::Milestone { name, week } => {
+                    let cx = x(*week) + week_width / 2.0;
+                    let cy = top + ROW_HEIGHT / 2.0;
+                    let r = 7.0;
+                    write!(
+                        svg,
+                        r##"<polygon points="{:.1},{cy:.1} {cx:.1},{:.1} {:.1},{cy:.1} {cx:.1},{:.1}" fill="#252525"><title>{}: week {week}</title></polygon>"##,
+                        cx - r,
+                        cy - r,
+                        cx + r,
+                        cy + r,
+                        escape_html(name)
+                    )
+                    .unwrap();
+                }
+            }
+        }
+
+        // a legend of the owners below the chart
+        let mut lx = plot_left;
+        let ly = plot_bottom + LEGEND_HEIGHT / 2.0;
+        for owner in &self.owners {
+            write!(
+                svg,
+                r#"<rect x="{lx:.1}" y="{:.1}" width="12" height="12" fill="{}"/><text x="{:.1}" y="{:.1}">{}</text>"#,
+                ly - 6.0,
+                self.owner_colour(owner),
+                lx + 18.0,
+                ly + 4.0,
+                escape_html(owner)
+            )
+            .unwrap();
+            lx += 18.0 + owner.chars().count() as f64 * CHAR_WIDTH + PADDING;
+        }
+
+        svg.push_str("</svg>");
+        svg
+    }
+
+    /// Draw the chart using TikZ, with the weeks sharing the part of the text
+    /// width not used by the task names
+    fn to_tikz(&self) -> String {
+        let mut tikz = String::new();
+        tikz.push_str("\\begin{tikzpicture}[font=\\small]\n");
+        writeln!(
+            tikz,
+            "\\pgfmathsetlengthmacro{{\\ganttweek}}{{0.7*\\linewidth/{}}}",
+            self.weeks()
+        )
+        .unwrap();
+        for (index, owner) in self.owners.iter().enumerate() {
+            writeln!(
+                tikz,
+                "\\definecolor{{gantt-owner-{index}}}{{HTML}}{{{}}}",
+                self.owner_colour(owner)[1..].to_uppercase()
+            )
+            .unwrap();
+        }
+        writeln!(
+            tikz,
+            "\\definecolor{{gantt-no-owner}}{{HTML}}{{{}}}",
+            NO_OWNER[1..].to_uppercase()
+        )
+        .unwrap();
+        tikz.push_str("\\begin{scope}[x=\\ganttweek, y=-0.6cm]\n");
+
+        let weeks = self.weeks();
+        let rows = self.rows.len();
+        let step = if weeks > 26 { (weeks + 25) / 26 } else { 1 };
+
+        writeln!(
+            tikz,
+            "\\node[anchor=east, font=\\small\\bfseries] at (0, -0.5) {{{}}};",
+            escape_latex(&self.week_label)
+        )
+        .unwrap();
+        for offset in 0..=weeks {
+            writeln!(
+                tikz,
+                "\\draw[black!15] ({offset}, 0) -- ({offset}, {rows});"
+            )
+            .unwrap();
+            if offset < weeks && offset % step == 0 {
+                writeln!(
+                    tikz,
+                    "\\node[font=\\footnotesize] at ({}.5, -0.5) {{{}}};",
+                    offset,
+                    self.first_week + offset
+                )
+                .unwrap();
+            }
+        }
+
+        for (index, row) in self.rows.iter().enumerate() {
+            let (Row::Task { name, .. } | Row::Milestone { name, .. }) = row;
+            writeln!(
+                tikz,
+                "\\node[anchor=east, align=right, text width=0.28\\linewidth] at (0, {index}.5) {{{}}};",
+                escape_latex(name)
+            )
+            .unwrap();
+
+            match row {
+                Row::Task {
+                    start, end, owners, ..
+                } => {
+                    let colours: Vec<String> = if owners.is_empty() {
+                        vec!["gantt-no-owner".to_string()]
+                    } else {
+                        owners
+                            .iter()
+                            .map(|owner| {
+                                let index =
+                                    self.owners.iter().position(|o| o == owner).unwrap_or(0);
+                                format!("gantt-owner-{index}")
+                            })
+                            .collect()
+                    };
+                    let stripe = 0.6 / colours.len() as f64;
+                    for (stripe_index, colour) in colours.iter().enumerate() {
+                        let top = index as f64 + 0.2 + stripe_index as f64 * stripe;
+                        writeln!(
+                            tikz,
+                            "\\fill[{colour}] ({}, {top:.3}) rectangle ({}, {:.3});",
+                            start - self.first_week,
+                            end - self.first_week + 1,
+                            top + stripe
+                        )
+                        .unwrap();
+                    }
+                }
+                Row::Milestone { week, .. } => {
+                    writeln!(
+                        tikz,
+                        "\\node[diamond, fill=black!85, inner sep=0pt, minimum size=2.5mm] at ({}.5, {index}.5) {{}};",
+                        week - self.first_week
+                    )
+                    .unwrap();
+                }
+            }
+        }
+
+        if !self.owners.is_empty() {
+            let legend = self
+                .owners
+                .iter()
+                .enumerate()
+                .map(|(index, owner)| {
+                    format!(
+                        "\\textcolor{{gantt-owner-{index}}}{{\\rule{{2mm}}{{2mm}}}}~{}",
+                        escape_latex(owner)
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join("\\quad ");
+            writeln!(
+                tikz,
+                "\\node[anchor=north west, inner xsep=0pt] at (0, {rows}.2) {{{legend}}};"
+            )
+            .unwrap();
+        }
+
+        tikz.push_str("\\end{scope}\n\\end{tikzpicture}\n");
+        tikz
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(text: &str) -> Result<Gantt, Error> {
+        Gantt::parse(text, &json!({}))
+    }
+
+    /// The error message of a plan that cannot be parsed
+    fn error(text: &str) -> String {
+        match parse(text) {
+            Err(Error::Gantt(message)) => message,
+            _ => panic!("'{text}' should not be parsed"),
+        }
+    }
+
+    #[test]
+    fn parses_tasks_and_milestones() {
+        let gantt = parse(
+            "# planning\n\nResearch | 2-4 | Alice, Bob\nmilestone: Draft | 6\n  Write | 5 | Bob,  \n",
+        )
+        .ok()
+        .unwrap();
+
+        assert_eq!(gantt.rows.len(), 3);
+        assert_eq!((gantt.first_week, gantt.last_week), (2, 6));
+        assert_eq!(gantt.weeks(), 5);
+        assert_eq!(gantt.owners, ["Alice", "Bob"]);
+        assert!(matches!(
+            &gantt.rows[2],
+            Row::Task { name, start: 5, end: 5, owners } if name == "Write" && owners == &["Bob"]
+        ));
+        assert!(matches!(&gantt.rows[1], Row::Milestone { name, week: 6 } if name == "Draft"));
+    }
+
+    #[test]
+    fn parses_tasks_without_owners() {
+        let gantt = parse("Task | 1 - 3").ok().unwrap();
+        assert!(gantt.owners.is_empty());
+        assert!(matches!(
+            &gantt.rows[0],
+            Row::Task {
+                start: 1,
+                end: 3,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn rejects_malformed_plans() {
+        assert_eq!(error(""), "The chart has no tasks");
+        assert_eq!(error("# only a comment"), "The chart has no tasks");
+        assert_eq!(error("Task"), "The task 'Task' is missing weeks");
+        assert_eq!(
+            error("milestone: Done"),
+            "The milestone 'Done' is missing a week"
+        );
+        assert_eq!(error("Task | x-3"), "Invalid week 'x' for 'Task'");
+        assert_eq!(error("Task | NaN"), "Invalid week 'NaN' for 'Task'");
+        assert_eq!(error("Task | 1.5"), "Invalid week '1.5' for 'Task'");
+        assert_eq!(
+            error("Task | 4-2"),
+            "'Task' ends in week 2, before it starts in week 4"
+        );
+    }
+
+    #[test]
+    fn escapes_names() {
+        let gantt = parse("R&D <1> | 1-2 | A_B").ok().unwrap();
+        assert!(gantt.to_tikz().contains(r"R\&D <1>"));
+        assert!(gantt.to_tikz().contains(r"A\_B"));
+        assert!(gantt.to_svg(640.0).contains("R&amp;D &lt;1&gt;"));
+    }
+}
@@ -0,0 +1,235 @@
+use crate::{escape::escape_html, Error};
+use serde_json::{json, Value};
+use std::env;
+
+/// An acronym declared using `[acronym-def]` or a term declared using `[gloss-def]`
+pub(crate) struct Entry {
+    pub(crate) key: String,
+    pub(crate) acronym: bool,
+    /// The expansion of an acronym, or the name of a term
+    pub(crate) long: String,
+    /// The description of a term
+    pub(crate) description: String,
+}
+
+impl Entry {
+    /// The id of the entry in the glossary, used to link uses to it
+    pub(crate) fn anchor(&self) -> String {
+        let key: String = self
+            .key
+            .chars()
+            .map(|c| if c.is_alphanumeric() { c } else { '-' })
+            .collect();
+        format!("gls:{key}")
+    }
+}
+
+/// Get all declared acronyms and terms, in the order they were declared
+pub(crate) fn get_entries() -> Vec<Entry> {
+    let var = env::var("glossary").unwrap_or_else(|_| "[]".to_string());
+    let entries: Vec<String> = serde_json::from_str(&var).unwrap();
+
+    entries
+        .iter()
+        .map(|entry| {
+            let entry: Value = serde_json::from_str(entry).unwrap();
+            let get = |key: &str| entry[key].as_str().unwrap_or_default().to_string();
+
+            Entry {
+                key: get("key"),
+                acronym: entry["acronym"].as_bool().unwrap_or_default(),
+                long: get("long"),
+                description: get("description"),
+            }
+        })
+        .collect()
+}
+
+/// Transform `[acronym-def]` and `[gloss-def]`, which declare an acronym or a term
+pub(crate) fn transform_glossary_def(acronym: bool, input: Value) -> Result<String, Error> {
+    let arguments = &input["arguments"];
+    let key = arguments["key"].as_str().unwrap_or_default().trim();
+    let data = input["data"].as_str().unwrap_or_default().trim();
+
+    let entry = if acronym {
+        json!({"key": key, "acronym": true, "long": data, "description": ""})
+    } else {
+        let name = arguments["name"].as_str().unwrap_or_default().trim();
+        json!({
+            "key": key,
+            "acronym": false,
+            "long": if name.is_empty() { key } else { name },
+            "description": data,
+        })
+    };
+
+    let json = json!([
+        {"name": "list-push", "arguments": {"name": "glossary"}, "data": entry.to_string()}
+    ]);
+    Ok(serde_json::to_string(&json).unwrap())
+}
+
+/// Transform `[acr]` and `[gls]`, which use an acronym or a term. Whether this
+/// is the first use isn't known until every use has been pushed to the list
+/// `glossary-uses`, so the text is written by `[glossary-use]` afterwards, in
+/// the same way as `[note-label]` does for notes.
+pub(crate) fn transform_glossary_use(input: Value) -> Result<String, Error> {
+    let key = input["data"].as_str().unwrap_or_default().trim();
+    let id = rand::random::<u64>();
+    let form = input["arguments"]["form"].as_str().unwrap_or("auto");
+
+    let json = json!([
+        {"name": "list-push", "arguments": {"name": "glossary-uses"}, "data": json!({"key": key, "id": id}).to_string()},
+        {"name": "glossary-use", "arguments": {"id": id, "form": form}, "data": key},
+    ]);
+    Ok(serde_json::to_string(&json).unwrap())
+}
+
+/// Transform `[glossary-use]`, writing an acronym in full on its first use
+/// and abbreviated after that, linked to its entry in the glossary
+pub(crate) fn transform_glossary_label(input: Value, to: &str) -> Result<String, Error> {
+    let key = input["data"].as_str().unwrap_or_default();
+    let id = input["arguments"]["id"].as_u64().unwrap();
+    let form = input["arguments"]["form"].as_str().unwrap_or("auto");
+
+    let entries = get_entries();
+    let Some(entry) = entries.iter().find(|entry| entry.key == key) else {
+        eprintln!("'{key}' is not declared using [acronym-def] or [gloss-def].");
+        let json = match to {
+            "latex" => json!([format!(r"\textbf{{{key}??}}")]),
+            _ => json!([format!("<strong>{key}??</strong>")]),
+        };
+        return Ok(serde_json::to_string(&json).unwrap());
+    };
+
+    let uses: Vec<String> = {
+        let var = env::var("glossary-uses").unwrap_or_else(|_| "[]".to_string());
+        serde_json::from_str(&var).unwrap()
+    };
+    let first_use = uses
+        .iter()
+        .map(|data| serde_json::from_str::<Value>(data).unwrap())
+        .find(|data| data["key"] == key)
+        .is_some_and(|data| data["id"].as_u64() == Some(id));
+
+    let text = match (entry.acronym, form) {
+        (false, _) => entry.long.clone(),
+        (true, "short") => entry.key.clone(),
+        (true, "long") => entry.long.clone(),
+        (true, "full") => format!("{} ({})", entry.long, entry.key),
+        (true, _) if first_use => format!("{} ({})", entry.long, entry.key),
+        (true, _) => entry.key.clone(),
+    };
+
+    let anchor = entry.anchor();
+    let json = match to {
+        "latex" => json!([
+            format!(r"\hyperlink{{{anchor}}}{{"),
+            {"name": "inline_content", "data": text},
+            "}",
+        ]),
+        "html" => json!([
+            format!(
+                r##"<a class="glossary-use" id="gls-use:{id}" href="#{anchor}" title="{}">"##,
+                escape_html(&entry.long)
+            ),
+            {"name": "inline_content", "data": text},
+            "</a>",
+        ]),
+        other => {
+            eprintln!("Cannot convert glossary use to {other}");
+            json!([])
+        }
+    };
+
+    Ok(serde_json::to_string(&json).unwrap())
+}
+
+/// Transform `[glossary]`, which lists all terms, or all acronyms using
+/// `type=acronyms`, in alphabetical order
+pub(crate) fn transform_glossary(input: Value, to: &str) -> Result<String, Error> {
+    let acronyms = input["arguments"]["type"].as_str() == Some("acronyms");
+    let json = Value::Array(glossary_list(acronyms, &[], to));
+    Ok(serde_json::to_string(&json).unwrap())
+}
+
+/// List the acronyms or the terms, together with any `extra` entries given
+/// as (abbreviation, expansion) pairs, sorted alphabetically
+pub(crate) fn glossary_list(acronyms: bool, extra: &[(String, String)], to: &str) -> Vec<Value> {
+    let entries = get_entries();
+    let mut items: Vec<(String, Option<String>, String)> = entries
+        .iter()
+        .filter(|entry| entry.acronym == acronyms)
+        .map(|entry| {
+            let (term, text) = if acronyms {
+                (entry.key.clone(), entry.long.clone())
+            } else {
+                (entry.long.clone(), entry.description.clone())
+            };
+            (term, Some(entry.anchor()), text)
+        })
+        .chain(
+            extra
+                .iter()
+                .map(|(term, text)| (term.clone(), None, text.clone())),
+        )
+        .collect();
+    items.sort_by_key(|(term, _, _)| term.to_lowercase());
+
+    let mut result = Vec::new();
+    match to {
+        // acronyms are short, so they are put in a table, while terms
+        // may have long descriptions that need to break across pages
+        "latex" if acronyms => {
+            result.push(json!(
+                "\\begin{tabular}{@{}p{0.2\\textwidth}p{0.75\\textwidth}@{}}\n"
+            ));
+            for (term, anchor, text) in items {
+                if let Some(anchor) = anchor {
+                    result.push(json!(format!(r"\hypertarget{{{anchor}}}{{}}")));
+                }
+                result.push(json!({"name": "inline_content", "data": term}));
+                result.push(json!(" & "));
+                result.push(json!({"name": "inline_content", "data": text}));
+                result.push(json!("\\\\\n"));
+            }
+            result.push(json!("\\end{tabular}\n"));
+        }
+        "latex" => {
+            result.push(json!("\\begin{description}\n"));
+            for (term, anchor, text) in items {
+                result.push(json!("\\item[{"));
+                if let Some(anchor) = anchor {
+                    result.push(json!(format!(r"\hypertarget{{{anchor}}}{{}}")));
+                }
+                result.push(json!({"name": "inline_content", "data": term}));
+                result.push(json!("}] "));
+                result.push(json!({"name": "block_content", "data": text}));
+                result.push(json!("\n"));
+            }
+            result.push(json!("\\end{description}\n"));
+        }
+        "html" => {
+            let class = if acronyms {
+                "abbreviations"
+            } else {
+                "glossary"
+            };
+            result.push(json!(format!(r#"<dl class="{class}">"#)));
+            for (term, anchor, text) in items {
+                match anchor {
+                    Some(anchor) => result.push(json!(format!(r#"<dt id="{anchor}">"#))),
+                    None => result.push(json!("<dt>")),
+                }
+                result.push(json!({"name": "inline_content", "data": term}));
+                result.push(json!("</dt><dd>"));
+                result.push(json!({"name": "inline_content", "data": text}));
+                result.push(json!("</dd>"));
+            }
+            result.push(json!("</dl>"));
+        }
+        other => eprintln!("Cannot convert glossary to {other}"),
+    }
+
+    result
+}
@@ -0,0 +1,232 @@
+use crate::{
+    bibtex,
+    citation_style::{CitationStyle, Part},
+    escape::escape_html,
+    DocSettings,
+};
+use serde_json::{json, Value};
+
+/// The institution, as it is written on the imprint page
+const INSTITUTION: &str = "Chalmers University of Technology and University of Gothenburg";
+const PLACE: &str = "Gothenburg, Sweden";
+pub(crate) const THESIS_TYPE: &str = "Bachelor's thesis";
+
+/// The thesis itself as a source, so that readers know how to cite it
+pub(crate) struct ThesisCitation {
+    key: String,
+    pub(crate) title: String,
+    /// The authors as (family name, given names)
+    pub(crate) authors: Vec<(String, String)>,
+    pub(crate) year: String,
+    department: Option<String>,
+}
+
+impl ThesisCitation {
+    pub(crate) fn from_settings(settings: &DocSettings) -> Self {
+        let title = match &settings.subtitle {
+            Some(subtitle) => format!("{}: {subtitle}", settings.get_title()),
+            None => settings.get_title(),
+        };
+
+        let authors: Vec<(String, String)> = settings
+            .authors
+            .iter()
+            .map(|author| match author.name.trim().rsplit_once(' ') {
+                Some((given, family)) => (family.to_string(), given.trim().to_string()),
+                None => (author.name.trim().to_string(), String::new()),
+            })
+            .collect();
+
+        // a key such as "adelhult2023modmark"
+        let first_word = |text: &str| -> String {
+            text.split_whitespace()
+                .next()
+                .unwrap_or_default()
+                .chars()
+                .filter(char::is_ascii_alphanumeric)
+                .collect::<String>()
+                .to_lowercase()
+        };
+        let year = settings.get_year();
+        let key = format!(
+            "{}{year}{}",
+            authors
+                .first()
+                .map(|(family, _)| first_word(family))
+                .unwrap_or_default(),
+            first_word(&title)
+        );
+
+        Self {
+            key,
+            title,
+            authors,
+            year,
+            department: settings.department.clone(),
+        }
+    }
+
+    pub(crate) fn institution(&self) -> String {
+        match &self.department {
+            Some(department) => format!("{department}, {INSTITUTION}"),
+            None => INSTITUTION.to_string(),
+        }
+    }
+
+    pub(crate) fn to_bibtex(&self) -> String {
+        let authors = self
+            .authors
+            .iter()
+            .map(|(family, given)| {
+                if given.is_empty() {
+                    format!("{{{family}}}")
+                } else {
+                    format!("{family}, {given}")
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(" and ");
+
+        let fields = [
+            ("author", authors),
+            ("title", format!("{{{}}}", self.title)),
+            ("type", THESIS_TYPE.to_string()),
+            ("institution", self.institution()),
+            ("address", PLACE.to_string()),
+            ("year", self.year.clone()),
+        ];
+
+        let mut bibtex = format!("@thesis{{{},\n", self.key);
+        for (name, value) in fields {
+            bibtex.push_str(&format!("  {name} = {{{}}},\n", escape_bibtex(&value)));
+        }
+        bibtex.push_str("}\n");
+        bibtex
+    }
+
+    pub(crate) fn to_ris(&self) -> String {
+        let mut lines = vec!["TY  - THES".to_string()];
+        for (family, given) in &self.authors {
+            if given.is_empty() {
+                lines.push(format!("AU  - {family}"));
+            } else {
+                lines.push(format!("AU  - {family}, {given}"));
+            }
+        }
+        lines.push(format!("TI  - {}", self.title));
+        lines.push(format!("PY  - {}", self.year));
+        lines.push(format!("PB  - {}", self.institution()));
+        lines.push(format!("CY  - {PLACE}"));
+        lines.push(format!("M3  - {THESIS_TYPE}"));
+        lines.push("ER  - ".to_string());
+        lines.join("\n") + "\n"
+    }
+
+    pub(crate) fn to_csl_json(&self) -> String {
+        let authors: Vec<Value> = self
+            .authors
+            .iter()
+            .map(|(family, given)| {
+                if given.is_empty() {
+                    json!({"literal": family})
+                } else {
+                    json!({"family": family, "given": given})
+                }
+            })
+            .collect();
+        let year: Value = self
+            .year
+            .parse::<u32>()
+            .map_or_else(|_| json!(self.year), |year| json!(year));
+
+        let csl = json!([{
+            "id": self.key,
+            "type": "thesis",
+            "genre": THESIS_TYPE,
+            "title": self.title,
+            "author": authors,
+            "issued": {"date-parts": [[year]]},
+            "publisher": self.institution(),
+            "publisher-place": PLACE,
+        }]);
+        serde_json::to_string_pretty(&csl).unwrap()
+    }
+
+    /// The "How to cite" section, with the reference formatted in the
+    /// citation style of the document and the entry in several formats
+    pub(crate) fn to_html(&self) -> Vec<Value> {
+        let mut result = vec![json!(
+            r#"<section class="how-to-cite"><h2>How to cite</h2>"#
+        )];
+
+        // the entry is read back by the same parser as the sources, so the
+        // reference looks like any other in the bibliography
+        let bibtex = self.to_bibtex();
+        if let Some(entry) = bibtex::parse(&bibtex)
+            .ok()
+            .and_then(|mut entries| entries.pop())
+        {
+            result.push(json!(r#"<p class="reference">"#));
+            for part in CitationStyle::from_env().format(&entry, "") {
+                match part {
+                    Part::Text(text) | Part::Url(text) => result.push(json!(escape_html(&text))),
+                    Part::Title(title) => result.push(json!(escape_html(&title))),
+                    Part::Italic(text) => {
+                        result.push(json!(format!("<i>{}</i>", escape_html(&text))))
+                    }
+                }
+            }
+            result.push(json!("</p>"));
+        }
+
+        let formats = [
+            ("BibTeX", "bib", "application/x-bibtex", bibtex.clone()),
+            (
+                "RIS",
+                "ris",
+                "application/x-research-info-systems",
+                self.to_ris(),
+            ),
+            (
+                "CSL-JSON",
+                "json",
+                "application/vnd.citationstyles.csl+json",
+                self.to_csl_json(),
+            ),
+        ];
+        for (name, extension, mime, text) in formats {
+            result.push(json!(format!(
+                r#"<details><summary>{name}</summary><button type="button" onclick="navigator.clipboard.writeText(this.nextElementSibling.textContent)">Copy</button><pre><code>{}</code></pre><a download="{}.{extension}" href="data:{mime};charset=utf-8,{}">Download</a></details>"#,
+                escape_html(&text),
+                self.key,
+                percent_encode(&text)
+            )));
+        }
+
+        result.push(json!("</section>"));
+        result
+    }
+}
+
+fn escape_bibtex(text: &str) -> String {
+    let mut escaped = String::new();
+    for c in text.chars() {
+        if "&%$#_".contains(c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// Encode text for a data URI
+fn percent_encode(text: &str) -> String {
+    text.bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                (byte as char).to_string()
+            }
+            _ => format!("%{byte:02X}"),
+        })
+        .collect()
+}
@@ -1,4 +1,12 @@
-use crate::{DocSettings, Error};
+use crate::{
+    author::{group_by_affiliation, Author},
+    contribution::ContributionReport,
+    front_matter::{parse_abbreviations, Page},
+    glossary::{get_entries, glossary_list},
+    how_to_cite::ThesisCitation,
+    metadata::html_metadata,
+    DocSettings, Error,
+};
 use serde_json::{json, Value};
 use std::env;
 
@@ -24,6 +32,7 @@ pub(crate) fn transform_document_html(mut input: Value) -> Result<String, Error>
 <meta charset="UTF-8">
 "#
     ))];
+    result.push(raw!(html_metadata(&settings)));
 
     // Add imports
     let mut imports = {
@@ -40,60 +49,53 @@ pub(crate) fn transform_document_html(mut input: Value) -> Result<String, Error>
 </head>
 <body>
 <article>
-<div class="cover">
 "#
     ));
 
-    // cover
-    if let Some(cover) = &settings.cover_art {
-        result.push(raw!(format!(r#"<img alt="Cover photo" src="{cover}"/>"#)));
-    }
-
-    // title
-    result.push(raw!(format!(r#"<h1 class="title">{title}</h1>"#)));
-
-    if let Some(subtitle) = settings.subtitle {
-        result.push(raw!(format!(r#"<div class="subtitle">{subtitle}</div>"#)));
-    }
-
-    // authors
-    result.push(raw!(r#"<ul class="authors">"#));
-    result.push(raw!(&settings
-        .authors
-        .iter()
-        .map(|name| format!(r#"<li>{name}</li>"#))
-        .collect::<Vec<_>>()
-        .join("\n")));
-    result.push(raw!("</ul>"));
-    result.push(raw!("</div>"));
+    let report = ContributionReport::from_env(&settings);
 
-    let include_preamble = settings.abstract_content.is_some()
-        || settings.sammandrag.is_some()
-        || settings.acknowledgements_content.is_some();
+    // The cover, title and imprint pages make up the cover, the other pages
+    // are put in the preamble that follows it
+    let mut wrapper: Option<&str> = None;
+    for page in &settings.front_matter {
+        let page_wrapper = match page {
+            Page::Cover | Page::Title | Page::Imprint => "cover",
+            // there is no table of contents in HTML
+            Page::Contents => continue,
+            _ => "preamble",
+        };
 
-    if include_preamble {
-        result.push(raw!(r#"<div class="preamble">"#));
-    }
-
-    // abstract
-    if let Some(abstract_content) = &settings.abstract_content {
-        result.push(raw!("<h2>Abstract</h2>"));
-        result.push(json!({"name": "block_content", "data": abstract_content, "args": {}}));
-    }
-
-    // Sammandrag (swe. abstract)
-    if let Some(sammandrag) = &settings.sammandrag {
-        result.push(raw!("<h2>Sammandrag</h2>"));
-        result.push(json!({"name": "block_content", "data": sammandrag, "args": {}}));
-    }
+        let mut page_content = match page {
+            Page::Cover => create_cover(&settings),
+            Page::Title => create_title(&settings),
+            Page::Imprint => create_imprint(&settings),
+            Page::HowToCite => ThesisCitation::from_settings(&settings).to_html(),
+            Page::Dedication => create_dedication(&settings),
+            Page::Epigraph => create_epigraph(&settings),
+            Page::Abstract => create_abstract(&settings),
+            Page::Preface => create_preface(&settings),
+            Page::Acknowledgements => create_acknowledgements(&settings),
+            Page::Contributions => match report.as_ref() {
+                Some(report) if settings.contribution_report == "front-matter" => report.to_html(),
+                _ => Vec::new(),
+            },
+            Page::Contents => unreachable!(),
+            Page::Abbreviations => create_abbreviations(&settings),
+        };
+        if page_content.is_empty() {
+            continue;
+        }
 
-    // acknowledgements
-    if let Some(acknowledgements) = &settings.acknowledgements_content {
-        result.push(raw!("<h2>Acknowledgements</h2>"));
-        result.push(json!({"name": "block_content", "data": acknowledgements, "args": {}}));
+        if wrapper != Some(page_wrapper) {
+            if wrapper.is_some() {
+                result.push(raw!("</div>"));
+            }
+            result.push(raw!(format!(r#"<div class="{page_wrapper}">"#)));
+            wrapper = Some(page_wrapper);
+        }
+        result.append(&mut page_content);
     }
-
-    if include_preamble {
+    if wrapper.is_some() {
         result.push(raw!("</div>"));
     }
 
@@ -106,6 +108,12 @@ pub(crate) fn transform_document_html(mut input: Value) -> Result<String, Error>
         }
     }
 
+    if let Some(report) = report.as_ref() {
+        if settings.contribution_report == "appendix" {
+            result.append(&mut report.to_html());
+        }
+    }
+
     // Footnotes
     let notes = DocSettings::get_notes();
     if !notes.is_empty() {
@@ -130,3 +138,184 @@ pub(crate) fn transform_document_html(mut input: Value) -> Result<String, Error>
 
     Ok(serde_json::to_string(&result).unwrap())
 }
+
+/// The name of an author followed by their programme, e-mail address and ORCID iD
+fn author_html(author: &Author) -> String {
+    let mut html = format!(r#"<span class="name">{}</span>"#, author.name);
+
+    if let Some(programme) = &author.programme {
+        html.push_str(&format!(r#" <span class="programme">{programme}</span>"#));
+    }
+    if let Some(email) = &author.email {
+        html.push_str(&format!(
+            r#" <a class="email" href="mailto:{email}">{email}</a>"#
+        ));
+    }
+    if let (Some(orcid), Some(url)) = (&author.orcid, author.orcid_url()) {
+        html.push_str(&format!(
+            r#" <a class="orcid" href="{url}" title="ORCID iD">{orcid}</a>"#
+        ));
+    }
+
+    html
+}
+
+fn create_cover(settings: &DocSettings) -> Vec<Value> {
+    match &settings.cover_art {
+        Some(cover) => vec![raw!(format!(r#"<img alt="Cover photo" src="{cover}"/>"#))],
+        None => Vec::new(),
+    }
+}
+
+fn create_title(settings: &DocSettings) -> Vec<Value> {
+    let mut result = vec![raw!(format!(
+        r#"<h1 class="title">{}</h1>"#,
+        settings.get_title()
+    ))];
+
+    if let Some(subtitle) = &settings.subtitle {
+        result.push(raw!(format!(r#"<div class="subtitle">{subtitle}</div>"#)));
+    }
+
+    // authors, grouped by affiliation if they have any
+    for (affiliation, authors) in group_by_affiliation(&settings.authors) {
+        result.push(raw!(r#"<ul class="authors">"#));
+        result.push(raw!(&authors
+            .iter()
+            .map(|author| format!("<li>{}</li>", author_html(author)))
+            .collect::<Vec<_>>()
+            .join("\n")));
+        result.push(raw!("</ul>"));
+        if let Some(affiliation) = affiliation {
+            result.push(raw!(format!(
+                r#"<div class="affiliation">{affiliation}</div>"#
+            )));
+        }
+    }
+
+    result
+}
+
+/// Supervisors and examiners
+fn create_imprint(settings: &DocSettings) -> Vec<Value> {
+    if settings.supervisors.is_empty() {
+        return Vec::new();
+    }
+
+    let mut result = vec![raw!(r#"<dl class="supervisors">"#)];
+    for person in &settings.supervisors {
+        result.push(raw!(format!(
+            "<div><dt>{}</dt><dd>{}</dd></div>",
+            person.role.label(),
+            person.name_and_organisation()
+        )));
+    }
+    result.push(raw!("</dl>"));
+    result
+}
+
+fn create_dedication(settings: &DocSettings) -> Vec<Value> {
+    let Some(dedication) = &settings.dedication else {
+        return Vec::new();
+    };
+
+    vec![
+        raw!(r#"<div class="dedication">"#),
+        json!({"name": "block_content", "data": dedication, "args": {}}),
+        raw!("</div>"),
+    ]
+}
+
+fn create_epigraph(settings: &DocSettings) -> Vec<Value> {
+    let Some(epigraph) = &settings.epigraph else {
+        return Vec::new();
+    };
+
+    let mut result = vec![
+        raw!(r#"<figure class="epigraph"><blockquote>"#),
+        json!({"name": "block_content", "data": epigraph, "args": {}}),
+        raw!("</blockquote>"),
+    ];
+    if let Some(attribution) = &settings.epigraph_attribution {
+        result.push(raw!("<figcaption>— "));
+        result.push(json!({"name": "inline_content", "data": attribution, "args": {}}));
+        result.push(raw!("</figcaption>"));
+    }
+    result.push(raw!("</figure>"));
+    result
+}
+
+/// The abstract, the sammandrag and any other abstracts
+fn create_abstract(settings: &DocSettings) -> Vec<Value> {
+    let mut result = Vec::new();
+
+    if let Some(abstract_content) = &settings.abstract_content {
+        result.push(raw!("<h2>Abstract</h2>"));
+        result.push(json!({"name": "block_content", "data": abstract_content, "args": {}}));
+    }
+
+    // Sammandrag (swe. abstract)
+    if let Some(sammandrag) = &settings.sammandrag {
+        result.push(raw!(r#"<section lang="sv"><h2>Sammandrag</h2>"#));
+        result.push(json!({"name": "block_content", "data": sammandrag, "args": {}}));
+        result.push(raw!("</section>"));
+    }
+
+    for abstract_ in &settings.abstracts {
+        result.push(raw!(format!(
+            r#"<section lang="{}" dir="{}"><h2>{}</h2>"#,
+            abstract_.language.replace('"', ""),
+            abstract_.direction(),
+            abstract_.heading
+        )));
+        result.push(json!({"name": "block_content", "data": abstract_.content, "args": {}}));
+        if let Some(keywords) = &abstract_.keywords {
+            result.push(raw!(format!(
+                r#"<p class="keywords"><strong>{}:</strong> {keywords}</p>"#,
+                abstract_.keywords_label()
+            )));
+        }
+        result.push(raw!("</section>"));
+    }
+
+    result
+}
+
+fn create_preface(settings: &DocSettings) -> Vec<Value> {
+    let Some(preface) = &settings.preface else {
+        return Vec::new();
+    };
+
+    vec![
+        raw!("<h2>Preface</h2>"),
+        json!({"name": "block_content", "data": preface, "args": {}}),
+    ]
+}
+
+fn create_acknowledgements(settings: &DocSettings) -> Vec<Value> {
+    let Some(acknowledgements) = &settings.acknowledgements_content else {
+        return Vec::new();
+    };
+
+    vec![
+        raw!("<h2>Acknowledgements</h2>"),
+        json!({"name": "block_content", "data": acknowledgements, "args": {}}),
+    ]
+}
+
+/// The abbreviations given by the constant `abbreviations` together with the
+/// acronyms declared using `[acronym-def]`
+fn create_abbreviations(settings: &DocSettings) -> Vec<Value> {
+    let abbreviations = settings
+        .abbreviations
+        .as_deref()
+        .map(parse_abbreviations)
+        .unwrap_or_default();
+    if abbreviations.is_empty() && !get_entries().iter().any(|entry| entry.acronym) {
+        return Vec::new();
+    }
+
+    let mut content = vec![raw!("<h2>List of Abbreviations</h2>")];
+    content.append(&mut glossary_list(true, &abbreviations, "html"));
+    content
+}
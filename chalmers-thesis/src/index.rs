@@ -0,0 +1,216 @@
+use crate::Error;
+use serde_json::{json, Value};
+use std::{collections::BTreeMap, env};
+
+/// An occurrence of a term marked using `[index]`
+struct IndexEntry {
+    term: String,
+    subterm: Option<String>,
+    see: Option<String>,
+    see_also: Option<String>,
+    id: u64,
+}
+
+/// Transform `[index]`, which marks an occurrence of a term, written as
+/// `term` or `term!subterm` like in makeindex. Nothing is shown in the text.
+pub(crate) fn transform_index(input: Value, to: &str) -> Result<String, Error> {
+    let data = input["data"].as_str().unwrap_or_default().trim();
+    let arguments = &input["arguments"];
+    let get = |key: &str| {
+        arguments[key]
+            .as_str()
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+    };
+
+    let (term, subterm) = match data.split_once('!') {
+        Some((term, subterm)) => (term.trim(), Some(subterm.trim())),
+        None => (data, None),
+    };
+    let see = get("see");
+    let see_also = get("see-also");
+    let id = rand::random::<u64>();
+
+    let mut result = vec![json!({
+        "name": "list-push",
+        "arguments": {"name": "index"},
+        "data": json!({"term": term, "subterm": subterm, "see": see, "see-also": see_also, "id": id}).to_string()
+    })];
+
+    match to {
+        "latex" => {
+            result.push(json!({"name": "set-add", "arguments": {"name": "imports"}, "data": "\\usepackage{imakeidx}\n\\makeindex[intoc]"}));
+
+            let mut key = quote_makeindex(term);
+            if let Some(subterm) = subterm {
+                key.push('!');
+                key.push_str(&quote_makeindex(subterm));
+            }
+            let encap = match (see, see_also) {
+                (Some(see), _) => format!("|see{{{see}}}"),
+                (None, Some(see_also)) => format!("|seealso{{{see_also}}}"),
+                (None, None) => String::new(),
+            };
+            result.push(json!(format!("\\index{{{key}{encap}}}")));
+        }
+        "html" => result.push(json!(format!(
+            r#"<span class="index-anchor" id="idx:{id}"></span>"#
+        ))),
+        other => eprintln!("Cannot convert index to {other}"),
+    }
+
+    Ok(serde_json::to_string(&result).unwrap())
+}
+
+/// Quote the characters that have a special meaning to makeindex
+fn quote_makeindex(text: &str) -> String {
+    text.chars()
+        .flat_map(|c| match c {
+            '!' | '@' | '|' | '"' => vec!['"', c],
+            c => vec![c],
+        })
+        .collect()
+}
+
+fn get_index_entries() -> Vec<IndexEntry> {
+    let var = env::var("index").unwrap_or_else(|_| "[]".to_string());
+    let entries: Vec<String> = serde_json::from_str(&var).unwrap();
+
+    entries
+        .iter()
+        .map(|entry| {
+            let entry: Value = serde_json::from_str(entry).unwrap();
+            let get = |key: &str| entry[key].as_str().map(str::to_string);
+
+            IndexEntry {
+                term: get("term").unwrap_or_default(),
+                subterm: get("subterm"),
+                see: get("see"),
+                see_also: get("see-also"),
+                id: entry["id"].as_u64().unwrap(),
+            }
+        })
+        .collect()
+}
+
+/// The occurrences of a term or subterm, and where else to look
+#[derive(Default)]
+struct IndexTerm {
+    ids: Vec<u64>,
+    see: Vec<String>,
+    see_also: Vec<String>,
+    /// Subterms sorted alphabetically, each with their original spelling
+    subterms: BTreeMap<String, (String, IndexTerm)>,
+}
+
+impl IndexTerm {
+    fn add(&mut self, entry: &IndexEntry) {
+        match &entry.see {
+            Some(see) if !self.see.contains(see) => self.see.push(see.clone()),
+            _ => {}
+        }
+        match &entry.see_also {
+            Some(see_also) if !self.see_also.contains(see_also) => {
+                self.see_also.push(see_also.clone())
+            }
+            _ => {}
+        }
+        // a cross reference is not an occurrence of the term
+        if entry.see.is_none() {
+            self.ids.push(entry.id);
+        }
+    }
+
+    fn to_html(&self, name: &str, result: &mut Vec<Value>) {
+        result.push(json!("<li>"));
+        result.push(json!({"name": "inline_content", "data": name}));
+
+        // the occurrences are numbered, since there are no pages to refer to
+        for (number, id) in self.ids.iter().enumerate() {
+            let separator = if number == 0 { " " } else { ", " };
+            result.push(json!(format!(
+                r##"{separator}<a href="#idx:{id}">{}</a>"##,
+                number + 1
+            )));
+        }
+        for (label, targets) in [("see", &self.see), ("see also", &self.see_also)] {
+            if !targets.is_empty() {
+                result.push(json!(format!(" <em>{label}</em> ")));
+                result.push(json!({"name": "inline_content", "data": targets.join(", ")}));
+            }
+        }
+
+        if !self.subterms.is_empty() {
+            result.push(json!("<ul>"));
+            for (name, term) in self.subterms.values() {
+                term.to_html(name, result);
+            }
+            result.push(json!("</ul>"));
+        }
+        result.push(json!("</li>"));
+    }
+}
+
+/// Transform `[print-index]`, which prints the index. In HTML the index is
+/// built by this package, with every term linking back to where it was marked.
+pub(crate) fn transform_print_index(_input: Value, to: &str) -> Result<String, Error> {
+    let json = match to {
+        "latex" => json!([
+            {"name": "set-add", "arguments": {"name": "imports"}, "data": "\\usepackage{imakeidx}\n\\makeindex[intoc]"},
+            "\\printindex\n",
+        ]),
+        "html" => {
+            // terms grouped by their first letter, both sorted alphabetically
+            let mut groups: BTreeMap<String, BTreeMap<String, (String, IndexTerm)>> =
+                BTreeMap::new();
+            for entry in get_index_entries() {
+                let letter = entry
+                    .term
+                    .chars()
+                    .next()
+                    .map(|c| {
+                        if c.is_alphabetic() {
+                            c.to_uppercase().to_string()
+                        } else {
+                            // numbers and symbols are put first
+                            "#".to_string()
+                        }
+                    })
+                    .unwrap_or_default();
+
+                let (_, term) = groups
+                    .entry(letter)
+                    .or_default()
+                    .entry(entry.term.to_lowercase())
+                    .or_insert_with(|| (entry.term.clone(), IndexTerm::default()));
+
+                match &entry.subterm {
+                    Some(subterm) => term
+                        .subterms
+                        .entry(subterm.to_lowercase())
+                        .or_insert_with(|| (subterm.clone(), IndexTerm::default()))
+                        .1
+                        .add(&entry),
+                    None => term.add(&entry),
+                }
+            }
+
+            let mut result = vec![json!(r#"<section class="index"><h1>Index</h1>"#)];
+            for (letter, terms) in &groups {
+                result.push(json!(format!("<h3>{letter}</h3><ul>")));
+                for (name, term) in terms.values() {
+                    term.to_html(name, &mut result);
+                }
+                result.push(json!("</ul>"));
+            }
+            result.push(json!("</section>"));
+            Value::Array(result)
+        }
+        other => {
+            eprintln!("Cannot convert index to {other}");
+            json!([])
+        }
+    };
+
+    Ok(serde_json::to_string(&json).unwrap())
+}
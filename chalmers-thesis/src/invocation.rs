@@ -0,0 +1,243 @@
+use std::{
+    fmt::{self, Display},
+    iter::Peekable,
+    str::Chars,
+};
+
+/// Characters that can be used to build a multiline delimiter. Opening
+/// brackets are closed by their counterpart, as described in the language
+/// chapter of the thesis.
+const DELIMITER_PAIRS: [(char, char); 3] = [('(', ')'), ('{', '}'), ('<', '>')];
+
+/// A builder for ModMark module invocations that are passed back to the
+/// compiler as `block_content` or `inline_content`.
+///
+/// Arguments are always quoted, and the body is wrapped in
+/// a delimiter that does not occur in the body itself, so that any user text
+/// (captions containing quotes, code containing `)))` and so on) survives the
+/// round trip.
+pub(crate) struct ModuleInvocation {
+    name: String,
+    positional_arguments: Vec<String>,
+    arguments: Vec<(String, String)>,
+    body: String,
+}
+
+impl ModuleInvocation {
+    pub(crate) fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            positional_arguments: Vec::new(),
+            arguments: Vec::new(),
+            body: String::new(),
+        }
+    }
+
+    /// Add a named argument to the invocation
+    pub(crate) fn arg(mut self, name: &str, value: impl ToString) -> Self {
+        self.arguments.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    /// Add arguments that are written in ModMark syntax, such as
+    /// `rust font_size="16"`. They are parsed and quoted again like the other
+    /// arguments, so that they can't end the invocation early.
+    pub(crate) fn args(mut self, text: &str) -> Result<Self, String> {
+        for (name, value) in parse_arguments(text)? {
+            match name {
+                Some(name) => self.arguments.push((name, value)),
+                None => self.positional_arguments.push(value),
+            }
+        }
+        Ok(self)
+    }
+
+    /// Set the text captured by the module
+    pub(crate) fn body(mut self, body: &str) -> Self {
+        self.body = body.to_string();
+        self
+    }
+
+    /// Find a multiline delimiter whose closing part does not occur in the body.
+    /// The delimiter is at least three characters long to match what the
+    /// fancy modules used before.
+    fn delimiter(&self) -> (String, String) {
+        (3..)
+            .flat_map(|len| {
+                DELIMITER_PAIRS.iter().map(move |&(open, close)| {
+                    (open.to_string().repeat(len), close.to_string().repeat(len))
+                })
+            })
+            .find(|(_, close)| !self.body.contains(close.as_str()))
+            .unwrap()
+    }
+}
+
+/// Quote an argument value, escaping backslashes and double quotes
+fn quote(value: &str) -> String {
+    let escaped = value.replace('\\', r"\\").replace('"', r#"\""#);
+    format!("\"{escaped}\"")
+}
+
+/// Parse arguments written in ModMark syntax into their names and values.
+/// Positional arguments have no name, and can't come after named ones.
+fn parse_arguments(text: &str) -> Result<Vec<(Option<String>, String)>, String> {
+    let mut chars = text.chars().peekable();
+    let mut arguments: Vec<(Option<String>, String)> = Vec::new();
+
+    let skip_whitespace = |chars: &mut Peekable<Chars>| {
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+    };
+    // a quoted value, with backslashes escaping the character after them, or
+    // a value that ends at whitespace, `=` or a quote
+    let value = |chars: &mut Peekable<Chars>| -> Result<String, String> {
+        let mut value = String::new();
+        if chars.next_if_eq(&'"').is_none() {
+            while let Some(c) = chars.next_if(|c| !c.is_whitespace() && !"=\"".contains(*c)) {
+                value.push(c);
+            }
+            return Ok(value);
+        }
+        loop {
+            match chars.next() {
+                Some('"') => return Ok(value),
+                Some('\\') => value.extend(chars.next()),
+                Some(c) => value.push(c),
+                None => return Err(format!("missing '\"' after \"{value}")),
+            }
+        }
+    };
+
+    loop {
+        skip_whitespace(&mut chars);
+        if chars.peek().is_none() {
+            break;
+        }
+
+        let first = value(&mut chars)?;
+        skip_whitespace(&mut chars);
+        if chars.next_if_eq(&'=').is_none() {
+            if arguments.iter().any(|(name, _)| name.is_some()) {
+                return Err(format!(
+                    "the positional argument '{first}' comes after a named argument"
+                ));
+            }
+            arguments.push((None, first));
+            continue;
+        }
+
+        if first.is_empty() {
+            return Err("an argument is missing its name".to_string());
+        }
+        skip_whitespace(&mut chars);
+        arguments.push((Some(first), value(&mut chars)?));
+    }
+
+    Ok(arguments)
+}
+
+impl Display for ModuleInvocation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[{}", self.name)?;
+        for value in &self.positional_arguments {
+            write!(f, " {}", quote(value))?;
+        }
+        for (name, value) in &self.arguments {
+            write!(f, " {name}={}", quote(value))?;
+        }
+
+        let (open, close) = self.delimiter();
+        write!(f, "]{open}\n{}\n{close}", self.body)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quotes_arguments() {
+        let invocation = ModuleInvocation::new("image")
+            .arg("caption", r#"A "quoted" word"#)
+            .arg("path", r"C:\figures\")
+            .arg("width", 0.5)
+            .body("cat.png");
+        assert_eq!(
+            invocation.to_string(),
+            "[image caption=\"A \\\"quoted\\\" word\" path=\"C:\\\\figures\\\\\" width=\"0.5\"](((\ncat.png\n)))"
+        );
+    }
+
+    #[test]
+    fn forwards_parsed_arguments() {
+        let invocation = ModuleInvocation::new("code")
+            .arg("caption", "Hello")
+            .args(r#" rust  font_size = "16" theme=github title="a \"b\" ]]]" "#)
+            .ok()
+            .unwrap();
+        assert_eq!(
+            invocation.to_string(),
+            "[code \"rust\" caption=\"Hello\" font_size=\"16\" theme=\"github\" title=\"a \\\"b\\\" ]]]\"](((\n\n)))"
+        );
+    }
+
+    #[test]
+    fn parses_arguments() {
+        assert_eq!(parse_arguments("").unwrap(), []);
+        assert_eq!(
+            parse_arguments(r#""C:\\dir" "" x="" y=\"#).unwrap(),
+            [
+                (None, r"C:\dir".to_string()),
+                (None, String::new()),
+                (Some("x".to_string()), String::new()),
+                (Some("y".to_string()), r"\".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_arguments() {
+        let error = |text: &str| parse_arguments(text).err().unwrap();
+        assert_eq!(error(r#"a="open"#), r#"missing '"' after "open"#);
+        assert_eq!(error("=rust"), "an argument is missing its name");
+        assert_eq!(
+            error("a=1 rust"),
+            "the positional argument 'rust' comes after a named argument"
+        );
+        assert_eq!(error("a==1"), "an argument is missing its name");
+    }
+
+    #[test]
+    fn writes_an_empty_body() {
+        assert_eq!(ModuleInvocation::new("toc").to_string(), "[toc](((\n\n)))");
+    }
+
+    #[test]
+    fn avoids_delimiters_in_the_body() {
+        let delimiters = |body: &str| {
+            let invocation = ModuleInvocation::new("code").body(body).to_string();
+            let open = invocation.find('\n').unwrap();
+            let close = invocation.rfind('\n').unwrap();
+            assert_eq!(&invocation[open + 1..close], body);
+            (
+                invocation["[code]".len()..open].to_string(),
+                invocation[close + 1..].to_string(),
+            )
+        };
+
+        assert_eq!(delimiters("f(g(x)))"), ("{{{".into(), "}}}".into()));
+        assert_eq!(delimiters("))) and }}}"), ("<<<".into(), ">>>".into()));
+        // an opening delimiter in the body doesn't end it
+        assert_eq!(delimiters("((( {{{"), ("(((".into(), ")))".into()));
+    }
+
+    #[test]
+    fn escalates_to_longer_delimiters() {
+        let invocation = ModuleInvocation::new("code").body(")))\n}}}\n>>>");
+        assert!(invocation.to_string().starts_with("[code]((((\n"));
+        assert!(invocation.to_string().ends_with("\n))))"));
+
+        let invocation = ModuleInvocation::new("code").body("))))\n}}}}\n>>>>");
+        assert!(invocation.to_string().starts_with("[code](((((\n"));
+    }
+}
@@ -1,61 +1,128 @@
-use crate::{DocSettings, Error};
+use crate::{
+    abstracts::{babel_language, language_tag},
+    author::{group_by_affiliation, Author},
+    bibliography::sources_path,
+    citation_style::CitationStyle,
+    contribution::ContributionReport,
+    engine::Engine,
+    front_matter::{parse_abbreviations, Page},
+    glossary::{get_entries, glossary_list},
+    metadata::{document_metadata, pdf_metadata},
+    page_layout::PageLayout,
+    preamble::Preamble,
+    svg::{include_svg, svg_to_pgf},
+    DocSettings, Error,
+};
 use serde_json::{json, Value};
-use std::{collections::HashSet, env, fmt::Write};
+use std::{env, fmt::Write};
 
 pub(crate) fn transform_document_latex(input: Value) -> Result<String, Error> {
     let settings = DocSettings::from_env();
 
     let mut content: Vec<Value> = Vec::new();
 
-    // Get a hashset of all imports that are needed
-    let imports = {
-        let mut imports = get_template_imports();
-        // add all imports coming from other packages
-        if let Ok(other_imports) = env::var("imports") {
-            if let Value::Array(array) = serde_json::from_str(&other_imports).unwrap() {
-                imports.extend(array.into_iter().map(|value| {
-                    if let Value::String(s) = value {
-                        s
-                    } else {
-                        unreachable!()
-                    }
-                }))
-            }
+    // Merge the imports of the template with the imports coming from other
+    // packages, which are sorted since they come from a set
+    let mut preamble = Preamble::default();
+    let engine = Engine::from_env();
+    let layout = PageLayout::from_env();
+    let languages = document_languages(&settings, engine);
+    let main_language = language_tag(languages.last().unwrap());
+    for import in get_template_imports(engine, layout, &languages) {
+        preamble.add(&import);
+    }
+    if let Ok(other_imports) = env::var("imports") {
+        let mut other_imports: Vec<String> = serde_json::from_str(&other_imports).unwrap();
+        other_imports.sort();
+        for import in other_imports {
+            preamble.add(&import);
         }
-        imports
-    };
+    }
+
+    // The engine, for editors that compile the document
+    content.push(Value::String(format!(
+        "% !TEX program = {}\n",
+        engine.program()
+    )));
+
+    // A PDF/A document is declared before the document class
+    if let Some(metadata) = document_metadata(&settings, main_language) {
+        content.push(Value::String(metadata));
+    }
 
     // Declare the document class
-    content.push(Value::String(
-        "\\documentclass[12pt,a4paper,twoside,openright]{report}\n\\usepackage[T1]{fontenc}\n\\usepackage{lmodern}\n\\usepackage{helvet}\n".into(),
-    ));
+    content.push(Value::String(format!(
+        "\\documentclass[12pt,a4paper,{}]{{report}}\n",
+        layout.class_options()
+    )));
 
-    // Add all the imports seperated by newlines
-    content.push(Value::String(
-        imports.into_iter().collect::<Vec<_>>().join("\n"),
-    ));
+    // Add the packages, in the order they have to be loaded in
+    content.push(Value::String(preamble.to_latex()));
+
+    // The sources are added after biblatex has been loaded
+    content.push(Value::String(format!(
+        "\n\\addbibresource{{{}}}\n",
+        sources_path()
+    )));
 
     // Add settings (helper macros and such)
+    content.push(Value::String(layout.definition().into()));
     content.push(Value::String(include_str!("settings.tex").into()));
+    content.push(Value::String(
+        CitationStyle::from_env().latex_patches().into(),
+    ));
 
-    // Start the document
-    content.push(Value::String("\\begin{document}\n".into()));
+    // The year on the title pages, if it isn't the current one
+    if let Some(year) = settings
+        .year
+        .as_ref()
+        .filter(|year| year.parse::<u32>().is_ok())
+    {
+        content.push(Value::String(format!("\\year={year}\n")));
+    }
 
-    // Add the cover, title and imprint pages
-    content.push(Value::String(create_coverpage(&settings)));
-    content.push(Value::String(create_titlepage(&settings)));
-    content.push(Value::String(create_imprint_page(&settings)));
+    // PDF metadata
+    content.push(Value::String(pdf_metadata(&settings, main_language)));
 
-    content.append(&mut create_abstract(&settings));
+    // Start the document
+    content.push(Value::String(
+        "\\begin{document}\n\\pagenumbering{roman}\n".into(),
+    ));
 
-    content.append(&mut create_acknowledgements(&settings));
+    let report = ContributionReport::from_env(&settings);
+
+    // Add the front matter pages, in the order they are listed
+    for page in &settings.front_matter {
+        match page {
+            Page::Cover => content.push(Value::String(create_coverpage(&settings, layout)?)),
+            Page::Title => content.push(Value::String(create_titlepage(&settings))),
+            Page::Imprint => content.push(Value::String(create_imprint_page(&settings))),
+            // the PDF is cited from its imprint page
+            Page::HowToCite => {}
+            Page::Dedication => content.append(&mut create_dedication(&settings)),
+            Page::Epigraph => content.append(&mut create_epigraph(&settings)),
+            Page::Abstract => content.append(&mut create_abstract(&settings, layout, engine)),
+            Page::Preface => content.append(&mut create_preface(&settings)),
+            Page::Acknowledgements => {
+                content.append(&mut create_acknowledgements(&settings, layout))
+            }
+            Page::Contributions => {
+                if let Some(report) = report.as_ref() {
+                    if settings.contribution_report == "front-matter" {
+                        content.append(&mut report.to_latex(false));
+                    }
+                }
+            }
+            Page::Contents => {
+                content.push(Value::String("\n\\newpage\n\\tableofcontents\n".into()))
+            }
+            Page::Abbreviations => content.append(&mut create_abbreviations(&settings)),
+        }
+    }
 
-    // table of contents and start of main content
+    // start of main content
     content.push(Value::String(
         r"
-\newpage
-\tableofcontents
-
 \cleardoublepage
 \setcounter{page}{1}
 \pagenumbering{arabic}
@@ -71,46 +138,94 @@ pub(crate) fn transform_document_latex(input: Value) -> Result<String, Error> {
         }
     };
 
+    if let Some(report) = report.as_ref() {
+        if settings.contribution_report == "appendix" {
+            content.append(&mut report.to_latex(true));
+        }
+    }
+
     content.push(Value::String("\\end{document}".into()));
 
     Ok(serde_json::to_string(&content).unwrap())
 }
 
-fn get_template_imports() -> HashSet<String> {
-    ["\\usepackage[top=3cm,bottom=3cm,inner=3cm,outer=3cm]{geometry}".to_string(),
-    "\\usepackage{parskip}".to_string(),
-    "\\usepackage{textcomp}".to_string(),
-    "\\usepackage[utf8]{inputenc}".to_string(),
-    "\\usepackage[english]{babel}".to_string(),
-    "\\usepackage{graphicx}".to_string(),
-    "\\usepackage{float}".to_string(),
-    "\\usepackage[hidelinks]{hyperref}".to_string(),
-    "\\usepackage[normalem]{ulem}".to_string(),
-    "\\usepackage{svg}".to_string(),
-    "\\usepackage{adjustbox}".to_string(),
-    "\\usepackage{datetime}".to_string(),
-    "\\usepackage{csquotes}".to_string(),
-    "\\usepackage[backend=biber,style=ieee,urldate=comp,block=ragged]{biblatex}".to_string(),
-    "\\usepackage{titlesec}".to_string(),
-    "\\usepackage{eso-pic}".to_string(),
-    "\\usepackage[labelfont=bf,textfont=normal,justification=justified,singlelinecheck=false]{caption}".to_string(),
-    "\\usepackage{fancyhdr}".to_string(), 
-    "\\usepackage{xpatch}".to_string()
-    ].into()
+/// The imports of the template, in the order they are loaded in unless a
+/// package has to be loaded before another, see [Preamble]
+fn get_template_imports(engine: Engine, layout: PageLayout, languages: &[&str]) -> Vec<String> {
+    let mut imports = engine.font_imports();
+    imports.extend([
+        "\\usepackage[top=3cm,bottom=3cm,inner=3cm,outer=3cm]{geometry}".to_string(),
+        "\\usepackage{parskip}".to_string(),
+        "\\usepackage{textcomp}".to_string(),
+        "\\usepackage{amssymb}".to_string(),
+        engine.language_import(languages),
+        "\\usepackage{graphicx}".to_string(),
+        "\\usepackage{pgf}".to_string(),
+        "\\usepackage{float}".to_string(),
+        layout.hyperref_import(),
+        "\\usepackage[normalem]{ulem}".to_string(),
+        "\\usepackage{adjustbox}".to_string(),
+        "\\usepackage{changepage}".to_string(),
+        "\\usepackage{pdflscape}".to_string(),
+        "\\usepackage{datetime}".to_string(),
+        "\\usepackage{csquotes}".to_string(),
+        CitationStyle::from_env().biblatex_import(),
+        "\\usepackage{titlesec}".to_string(),
+        "\\usepackage{eso-pic}".to_string(),
+        "\\usepackage[labelfont=bf,textfont=normal,justification=justified,singlelinecheck=false]{caption}".to_string(),
+        "\\usepackage{fancyhdr}".to_string(),
+        "\\usepackage{xpatch}".to_string(),
+    ]);
+    imports
 }
 
-fn create_coverpage(settings: &DocSettings) -> String {
+/// The languages to load babel or polyglossia with, the language of every
+/// abstract, where the last one (English) is the main language of the document
+fn document_languages(settings: &DocSettings, engine: Engine) -> Vec<&'static str> {
+    let mut languages: Vec<&str> = Vec::new();
+    if settings.sammandrag.is_some() {
+        languages.push("swedish");
+    }
+    for abstract_ in &settings.abstracts {
+        match babel_language(&abstract_.language) {
+            Some(language) if !engine.supports_language(language) => eprintln!(
+                "The language '{}' needs the engine xelatex or lualatex, the abstract is typeset without hyphenation rules for it.",
+                abstract_.language
+            ),
+            Some(language) if !languages.contains(&language) => languages.push(language),
+            Some(_) => {}
+            None => eprintln!(
+                "Unknown language '{}', the abstract is typeset without hyphenation rules for it.",
+                abstract_.language
+            ),
+        }
+    }
+    languages.retain(|language| *language != "english");
+    languages.push("english");
+
+    languages
+}
+
+fn create_coverpage(settings: &DocSettings, layout: PageLayout) -> Result<String, Error> {
     let mut content = String::new();
     content.push_str(
         r#"
-\pagenumbering{roman}	
 \begin{titlepage}
     \newgeometry{top=3cm, bottom=3cm, left=2.25 cm, right=2.25cm}	% Temporarily change margins
-    \AddToShipoutPicture*{\put(55,150){\includesvg{coverpage.svg}}}
+    \AddToShipoutPicture*{\put(55,150){"#,
+    );
+    content.push_str(&logo(include_str!("logos/coverpage.svg")));
+    content.push_str(
+        r#"}}
     \addtolength{\voffset}{2cm}"#,
     );
 
     if let Some(cover_art) = &settings.cover_art {
+        let cover_art = if cover_art.ends_with(".svg") {
+            include_svg(cover_art, "\\linewidth")?
+        } else {
+            format!("\\includegraphics[width=\\linewidth]{{{cover_art}}}")
+        };
         write!(
             &mut content,
             r#"
@@ -118,7 +233,7 @@ fn create_coverpage(settings: &DocSettings) -> String {
     % Cover art		
     \begin{{figure}}[H]
         \centering 
-        \includegraphics[width=\linewidth]{{{cover_art}}}
+        {cover_art}
     \end{{figure}}"#,
         )
         .unwrap();
@@ -177,7 +292,12 @@ fn create_coverpage(settings: &DocSettings) -> String {
         &settings
             .authors
             .iter()
-            .map(|name| format!(r#" {{\Large {name}}} \setlength{{\parskip}}{{2.9cm}}"#))
+            .map(|author| {
+                format!(
+                    r#" {{\Large {}}} \setlength{{\parskip}}{{2.9cm}}"#,
+                    author.name
+                )
+            })
             .collect::<Vec<_>>()
             .join("\\\\[1ex]\n"),
     );
@@ -195,14 +315,11 @@ fn create_coverpage(settings: &DocSettings) -> String {
     
     \renewcommand{\familydefault}{\rmdefault} \normalfont % Reset standard font
 \end{titlepage}
-    
-\newpage
-\restoregeometry
-\thispagestyle{empty}
-\mbox{}",
+\restoregeometry",
     );
+    content.push_str(layout.blank_back_page());
 
-    content
+    Ok(content)
 }
 
 fn create_titlepage(settings: &DocSettings) -> String {
@@ -243,7 +360,7 @@ fn create_titlepage(settings: &DocSettings) -> String {
         &settings
             .authors
             .iter()
-            .map(|name| format!(r#" {{\large {name}}}"#))
+            .map(|author| format!(r#" {{\large {}}}"#, author.name))
             .collect::<Vec<_>>()
             .join("\\\\[1ex]\n"),
     );
@@ -254,7 +371,7 @@ fn create_titlepage(settings: &DocSettings) -> String {
         r"
 \begin{{figure}}[H]
     \centering
-    \includesvg[width=0.25\pdfpagewidth]{{guandchalmerslogo}}
+    \resizebox{{0.25\paperwidth}}{{!}}{{{}}}
 \end{{figure}} \vspace{{5mm}}	
 	
     {department}\\
@@ -262,12 +379,19 @@ fn create_titlepage(settings: &DocSettings) -> String {
 	\textsc{{University of Gothenburg}} \\
 	Gothenburg, Sweden \the\year \\
 \end{{center}}",
-        department = format!("{}\\", &settings.department.to_owned().unwrap_or_default()),
+        logo(include_str!("logos/guandchalmerslogo.svg")),
+        department = settings.department.to_owned().unwrap_or_default() + "\\",
     ));
 
     content
 }
 
+/// A logo bundled with the package, drawn using PGF so that LaTeX doesn't
+/// need `--shell-escape`
+fn logo(svg: &str) -> String {
+    svg_to_pgf(svg).unwrap()
+}
+
 fn create_imprint_page(settings: &DocSettings) -> String {
     let mut content = String::new();
 
@@ -293,10 +417,10 @@ fn create_imprint_page(settings: &DocSettings) -> String {
             .authors
             .iter()
             .enumerate()
-            .map(|(i, name)| {
+            .map(|(i, author)| {
                 format!(
                     r#"{name} \setlength{{\parskip}}{{1cm}}"#,
-                    name = name.replace(" ", "~") + if i % 4 == 3 { r"\\" } else { "" }
+                    name = author.name.replace(" ", "~") + if i % 4 == 3 { r"\\" } else { "" }
                 )
             })
             .collect::<Vec<_>>()
@@ -312,7 +436,7 @@ fn create_imprint_page(settings: &DocSettings) -> String {
         &settings
             .authors
             .iter()
-            .map(|name| name.replace(" ", "~").to_uppercase())
+            .map(|author| author.name.replace(" ", "~").to_uppercase())
             .collect::<Vec<_>>()
             .join(", "),
     );
@@ -320,38 +444,30 @@ fn create_imprint_page(settings: &DocSettings) -> String {
 
     content.push_str("\n\n");
 
-    if let Some(supervisor) = &settings.supervisor {
-        let department = settings
-            .supervisor_department
-            .to_owned()
-            .unwrap_or_default();
-
-        writeln!(
-            &mut content,
-            "Supervisor (handledare): {supervisor}, {department}\\\\"
-        )
-        .unwrap();
-    }
-
-    if let Some(course_examiner) = &settings.course_examiner {
-        let department = settings
-            .course_examiner_department
-            .to_owned()
-            .unwrap_or_default();
-
-        writeln!(
-            &mut content,
-            "Examiner: {course_examiner}, {department}\\\\"
-        )
-        .unwrap();
+    // Contact details of the authors, grouped by affiliation
+    if settings
+        .authors
+        .iter()
+        .any(|author| author.has_details() || author.affiliation.is_some())
+    {
+        content.push_str("{\\small\n");
+        for (affiliation, authors) in group_by_affiliation(&settings.authors) {
+            for author in authors {
+                writeln!(&mut content, "{}\\\\", author_latex(author)).unwrap();
+            }
+            if let Some(affiliation) = affiliation {
+                writeln!(&mut content, "\\textit{{{affiliation}}}\\\\[1ex]").unwrap();
+            }
+        }
+        content.push_str("}\\setlength{\\parskip}{1cm}\n\n");
     }
 
-    if let Some(examiner) = &settings.examiner {
-        let department = settings.examiner_department.to_owned().unwrap_or_default();
-
+    for person in &settings.supervisors {
         writeln!(
             &mut content,
-            "Graded by teacher (rättande lärare): {examiner}, {department}\\\\"
+            "{}: {}\\\\",
+            person.role.label(),
+            person.name_and_organisation()
         )
         .unwrap();
     }
@@ -383,9 +499,12 @@ Telephone +46 31 772 1000 \setlength{\parskip}{0.5cm}
 
     content.push_str("\n\n");
 
+    content.push_str(&format!(
+        "\n\\resizebox{{5cm}}{{!}}{{{}}}\\\\",
+        logo(include_str!("logos/modmark.svg"))
+    ));
     content.push_str(
         r"
-\includesvg[width=5cm]{modmark}\\
 Typeset using \LaTeX \\
 Gothenburg, Sweden \the\year",
     );
@@ -393,7 +512,24 @@ Gothenburg, Sweden \the\year",
     content
 }
 
-fn create_acknowledgements(settings: &DocSettings) -> Vec<Value> {
+/// The name of an author followed by their programme, e-mail address and ORCID iD
+fn author_latex(author: &Author) -> String {
+    let mut details = vec![author.name.replace(' ', "~")];
+
+    if let Some(programme) = &author.programme {
+        details.push(programme.clone());
+    }
+    if let Some(email) = &author.email {
+        details.push(format!(r"\href{{mailto:{email}}}{{\nolinkurl{{{email}}}}}"));
+    }
+    if let (Some(orcid), Some(url)) = (&author.orcid, author.orcid_url()) {
+        details.push(format!(r"ORCID~\href{{{url}}}{{{orcid}}}"));
+    }
+
+    details.join(", ")
+}
+
+fn create_acknowledgements(settings: &DocSettings, layout: PageLayout) -> Vec<Value> {
     let mut content = Vec::new();
     let Some(acknowledgements) = settings.acknowledgements_content.to_owned() else {
         return content;
@@ -416,7 +552,7 @@ fn create_acknowledgements(settings: &DocSettings) -> Vec<Value> {
         settings
             .authors
             .iter()
-            .map(|name| name.replace(" ", "~"))
+            .map(|author| author.name.replace(" ", "~"))
             .collect::<Vec<_>>()
             .join(", "),
     ));
@@ -424,24 +560,92 @@ fn create_acknowledgements(settings: &DocSettings) -> Vec<Value> {
     content.push(Value::String(
         r"
 Gothenburg, \monthname \space \the\year
-\end{flushright}
-    
-\newpage				% Create empty back of side
-\thispagestyle{empty}
-\mbox{}"
+\end{flushright}"
             .to_string(),
     ));
 
+    // Create empty back of side
+    content.push(Value::String(layout.blank_back_page().to_string()));
+
     content
 }
 
-fn create_abstract(settings: &DocSettings) -> Vec<Value> {
+fn create_dedication(settings: &DocSettings) -> Vec<Value> {
+    let Some(dedication) = &settings.dedication else {
+        return Vec::new();
+    };
+
+    vec![
+        Value::String(
+            "\\newpage\n\\thispagestyle{empty}\n\\vspace*{0.3\\textheight}\n\\begin{center}\n\\itshape\n"
+                .to_string(),
+        ),
+        json!({"name": "block_content", "data": dedication, "args": {}}),
+        Value::String("\n\\end{center}\n".to_string()),
+    ]
+}
+
+fn create_epigraph(settings: &DocSettings) -> Vec<Value> {
+    let Some(epigraph) = &settings.epigraph else {
+        return Vec::new();
+    };
+
+    let mut content = vec![
+        Value::String(
+            "\\newpage\n\\thispagestyle{empty}\n\\vspace*{0.3\\textheight}\n\\begin{flushright}\n\\begin{minipage}{0.6\\textwidth}\n\\itshape\n"
+                .to_string(),
+        ),
+        json!({"name": "block_content", "data": epigraph, "args": {}}),
+    ];
+
+    if let Some(attribution) = &settings.epigraph_attribution {
+        content.push(Value::String("\n\n\\raggedleft\\upshape --- ".to_string()));
+        content.push(json!({"name": "inline_content", "data": attribution, "args": {}}));
+    }
+
+    content.push(Value::String(
+        "\n\\end{minipage}\n\\end{flushright}\n".to_string(),
+    ));
+    content
+}
+
+fn create_preface(settings: &DocSettings) -> Vec<Value> {
+    let Some(preface) = &settings.preface else {
+        return Vec::new();
+    };
+
+    vec![
+        Value::String("\\newpage\n\\thispagestyle{plain}\n\n\\section*{Preface}\n".to_string()),
+        json!({"name": "block_content", "data": preface, "args": {}}),
+    ]
+}
+
+/// The abbreviations given by the constant `abbreviations` together with the
+/// acronyms declared using `[acronym-def]`
+fn create_abbreviations(settings: &DocSettings) -> Vec<Value> {
+    let abbreviations = settings
+        .abbreviations
+        .as_deref()
+        .map(parse_abbreviations)
+        .unwrap_or_default();
+    if abbreviations.is_empty() && !get_entries().iter().any(|entry| entry.acronym) {
+        return Vec::new();
+    }
+
+    let mut content = vec![Value::String(
+        "\\newpage\n\\thispagestyle{plain}\n\n\\section*{List of Abbreviations}\n".to_string(),
+    )];
+    content.append(&mut glossary_list(true, &abbreviations, "latex"));
+    content
+}
+
+fn create_abstract(settings: &DocSettings, layout: PageLayout, engine: Engine) -> Vec<Value> {
     let mut content = Vec::new();
 
-    let Some(abstract_content) = &settings.abstract_content else {
+    if settings.abstract_content.is_none() && settings.abstracts.is_empty() {
         // Just return an empty string if there is no abstract defined
         return content;
-    };
+    }
     content.push(Value::String("\\newpage\n".to_string()));
     content.push(Value::String(format!(
         "\\textbf{{{}}}",
@@ -461,7 +665,7 @@ fn create_abstract(settings: &DocSettings) -> Vec<Value> {
         settings
             .authors
             .iter()
-            .map(|name| name.replace(" ", "~"))
+            .map(|author| author.name.replace(" ", "~"))
             .collect::<Vec<_>>()
             .join(", "),
     ));
@@ -478,18 +682,24 @@ Chalmers University of Technology and University of Gothenburg\setlength{\parski
 
 \thispagestyle{plain}
 \setlength{\parskip}{0pt plus 1.0pt}
-\section*{Abstract}
 "
         .to_string(),
     ));
 
     // Add the contents of the abstract
-    content.push(json!({"name": "block_content", "data": abstract_content, "args": {}}));
+    if let Some(abstract_content) = &settings.abstract_content {
+        content.push(Value::String("\\section*{Abstract}\n".to_string()));
+        content.push(json!({"name": "block_content", "data": abstract_content, "args": {}}));
+    }
 
     // If there is an abstract in swedish add that too
     if let Some(sammandrag) = &settings.sammandrag {
-        content.push(Value::String("\\section*{Sammandrag}".to_string()));
+        content.push(Value::String(format!(
+            "{}\n\\section*{{Sammandrag}}",
+            engine.begin_other_language("swedish")
+        )));
         content.push(json!({"name": "block_content", "data": sammandrag, "args": {}}));
+        content.push(Value::String("\n\\end{otherlanguage}\n".to_string()));
     }
 
     if let Some(keywords) = &settings.keywords {
@@ -500,14 +710,37 @@ Chalmers University of Technology and University of Gothenburg\setlength{\parski
         )));
     }
 
+    // Any other abstracts follow on pages of their own
+    for abstract_ in &settings.abstracts {
+        let language = babel_language(&abstract_.language)
+            .filter(|language| engine.supports_language(language));
+        content.push(Value::String(
+            "\n\\newpage\n\\thispagestyle{plain}\n".to_string(),
+        ));
+        if let Some(language) = language {
+            content.push(Value::String(format!(
+                "{}\n",
+                engine.begin_other_language(language)
+            )));
+        }
+        content.push(Value::String(format!(
+            "\\section*{{{}}}\n",
+            abstract_.heading
+        )));
+        content.push(json!({"name": "block_content", "data": abstract_.content, "args": {}}));
+        if let Some(keywords) = &abstract_.keywords {
+            content.push(Value::String(format!(
+                "\n\\vfill\n{}: {keywords}\n",
+                abstract_.keywords_label()
+            )));
+        }
+        if language.is_some() {
+            content.push(Value::String("\n\\end{otherlanguage}\n".to_string()));
+        }
+    }
+
     // Finally, add a empty back page
-    content.push(Value::String(
-        r"
-\newpage
-\thispagestyle{empty}
-\mbox{}"
-            .to_string(),
-    ));
+    content.push(Value::String(layout.blank_back_page().to_string()));
 
     content
 }
@@ -0,0 +1,291 @@
+use crate::{
+    float::{float_label, register_float, FloatKind, Layout},
+    Error,
+};
+use serde_json::{json, Value};
+
+/// Arguments only used by the long mode of `[fancy-big-table]`, which are
+/// removed before the table is passed on to std `[big-table]`
+pub(crate) const LONG_TABLE_ARGUMENTS: [&str; 3] = ["long", "header-rows", "scroll-height"];
+
+/// Transform `[fancy-big-table long=true]`. The table is rendered by this
+/// package instead of std `[big-table]`, using `longtable` in LaTeX so that it
+/// may span several pages, and a table with a sticky header in HTML.
+pub(crate) fn transform_long_table(input: Value, to: &str) -> Result<String, Error> {
+    let data = input["data"].as_str().unwrap();
+    let arguments = &input["arguments"];
+
+    let kind = FloatKind::find("big-table").unwrap();
+    let caption = arguments["caption"].as_str().unwrap_or_default();
+    let label = float_label(&kind, arguments);
+    let col_delimiter = arguments["column-delimiter"].as_str().unwrap();
+    let row_delimiter = arguments["row-delimiter"].as_str().unwrap();
+    let borders = arguments["borders"].as_str().unwrap_or("all");
+    let header_rows = arguments["header-rows"].as_u64().unwrap_or(1) as usize;
+
+    let rows = parse_rows(data, row_delimiter, col_delimiter)?;
+    let columns = rows.iter().map(Vec::len).max().unwrap_or(0);
+    let scroll_height = match arguments["scroll-height"].as_str().unwrap_or_default() {
+        "" => None,
+        height => Some(css_length(height).ok_or_else(|| {
+            Error::LongTable(format!(
+                "the scroll height '{height}' is not a CSS length such as '30em'"
+            ))
+        })?),
+    };
+    let alignments = column_alignments(arguments["alignment"].as_str().unwrap_or("left"), columns);
+    let (header, body) = rows.split_at(header_rows.min(rows.len()));
+
+    let mut result = register_float(&kind, &label, caption);
+
+    let layout = Layout::from_arguments(arguments)?;
+    result.append(&mut layout.open(to));
+
+    match to {
+        "html" => {
+            let escaped_label = label.replace('"', "%22");
+            match scroll_height {
+                None => result.push(json!(r#"<div class="long-table">"#)),
+                Some(height) => result.push(json!(format!(
+                    r#"<div class="long-table scroll" style="max-height: {height}">"#
+                ))),
+            }
+            result.push(json!(format!(
+                r#"<table class="borders-{borders}" id="{escaped_label}"><caption>"#
+            )));
+            result.push(json!({"name": "inline_content", "data": format!("**{} [element-number]({label}):** {caption}", kind.display_name)}));
+            result.push(json!("</caption>"));
+
+            for (rows, section, cell) in [(header, "thead", "th"), (body, "tbody", "td")] {
+                result.push(json!(format!("<{section}>")));
+                for row in rows {
+                    result.push(json!("<tr>"));
+                    for (index, content) in row.iter().enumerate() {
+                        let align = match alignments[index] {
+                            'c' => "center",
+                            'r' => "right",
+                            _ => "left",
+                        };
+                        result.push(json!(format!(r#"<{cell} style="text-align: {align}">"#)));
+                        result.push(json!({"name": "block_content", "data": content}));
+                        result.push(json!(format!("</{cell}>")));
+                    }
+                    result.push(json!("</tr>"));
+                }
+                result.push(json!(format!("</{section}>")));
+            }
+
+            result.push(json!("</table></div>"));
+        }
+        "latex" => {
+            result.push(json!({"name": "set-add", "arguments": {"name": "imports"}, "data": "\\usepackage{longtable}\n\\usepackage{array}"}));
+
+            let vertical = if matches!(borders, "all" | "vertical") {
+                "|"
+            } else {
+                ""
+            };
+            let outer = if borders == "none" || borders == "horizontal" {
+                ""
+            } else {
+                "|"
+            };
+            let rule = if matches!(borders, "all" | "horizontal") {
+                "\\hline\n"
+            } else {
+                ""
+            };
+            let outer_rule = if borders == "none" || borders == "vertical" {
+                ""
+            } else {
+                "\\hline\n"
+            };
+
+            // the header is always separated from the body, unless there are no borders at all
+            let header_rule = if borders == "none" { "" } else { "\\hline\n" };
+
+            // share the text width evenly between the columns, since cells
+            // contain block content they need a paragraph column
+            let spec = alignments
+                .iter()
+                .map(|alignment| {
+                    let align = match alignment {
+                        'c' => r"\centering",
+                        'r' => r"\raggedleft",
+                        _ => r"\raggedright",
+                    };
+                    format!(
+                        r">{{{align}\arraybackslash}}p{{\dimexpr\linewidth/{columns}-2\tabcolsep\relax}}"
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join(vertical);
+
+            result.push(json!(format!(
+                "\\begin{{longtable}}{{{outer}{spec}{outer}}}\n\\caption{{"
+            )));
+            result.push(json!({"name": "inline_content", "data": caption}));
+            result.push(json!(format!(
+                "}}\\label{{{}}}\\\\\n{outer_rule}",
+                label.replace('"', "%22")
+            )));
+            push_latex_rows(&mut result, header, rule);
+            result.push(json!(header_rule));
+            result.push(json!("\\endfirsthead\n"));
+
+            // the header that is repeated on every following page, where the
+            // empty short caption keeps it out of the list of tables
+            result.push(json!("\\caption[]{"));
+            result.push(json!({"name": "inline_content", "data": caption}));
+            result.push(json!(format!(" (continued)}}\\\\\n{outer_rule}")));
+            push_latex_rows(&mut result, header, rule);
+            result.push(json!(header_rule));
+            result.push(json!("\\endhead\n"));
+
+            result.push(json!(format!(
+                "{outer_rule}\\multicolumn{{{columns}}}{{r}}{{(continued on next page)}}\\\\\n\\endfoot\n{outer_rule}\\endlastfoot\n"
+            )));
+            push_latex_rows(&mut result, body, rule);
+            result.push(json!("\\end{longtable}\n"));
+        }
+        other => eprintln!("Cannot convert long table to {other}"),
+    }
+
+    result.append(&mut layout.close(to));
+
+    Ok(serde_json::to_string(&result).unwrap())
+}
+
+/// Split the content of a long table into rows of cells, skipping empty rows
+fn parse_rows<'a>(
+    data: &'a str,
+    row_delimiter: &str,
+    col_delimiter: &str,
+) -> Result<Vec<Vec<&'a str>>, Error> {
+    let rows: Vec<Vec<&str>> = data
+        .split(row_delimiter)
+        .filter(|row| !row.trim().is_empty())
+        .map(|row| row.split(col_delimiter).map(str::trim).collect())
+        .collect();
+
+    if rows.is_empty() {
+        return Err(Error::LongTable("the table has no rows".to_string()));
+    }
+    Ok(rows)
+}
+
+/// Check that a value is a CSS length, a positive number followed by a unit
+fn css_length(value: &str) -> Option<&str> {
+    const UNITS: [&str; 12] = [
+        "px", "em", "rem", "ex", "ch", "lh", "vh", "vw", "%", "pt", "cm", "mm",
+    ];
+
+    let value = value.trim();
+    let unit_start = value.find(|c: char| !c.is_ascii_digit() && c != '.')?;
+    let (number, unit) = value.split_at(unit_start);
+    let number: f64 = number.parse().ok()?;
+    (number > 0.0 && number.is_finite() && UNITS.contains(&unit)).then_some(value)
+}
+
+/// Push the rows of a longtable, where every cell is block content. The
+/// rule is put between rows, but not after the last one.
+fn push_latex_rows(result: &mut Vec<Value>, rows: &[Vec<&str>], rule: &str) {
+    for (row_index, row) in rows.iter().enumerate() {
+        if row_index > 0 {
+            result.push(json!(rule));
+        }
+        for (index, content) in row.iter().enumerate() {
+            if index > 0 {
+                result.push(json!(" & "));
+            }
+            result.push(json!({"name": "block_content", "data": content}));
+        }
+        result.push(json!("\\\\\n"));
+    }
+}
+
+/// Get the alignment of each column, either from one of left/center/right
+/// applying to all columns or from one of l/c/r for each column
+fn column_alignments(alignment: &str, columns: usize) -> Vec<char> {
+    let all = match alignment {
+        "left" => Some('l'),
+        "center" => Some('c'),
+        "right" => Some('r'),
+        _ => None,
+    };
+
+    (0..columns)
+        .map(|index| all.or_else(|| alignment.chars().nth(index)).unwrap_or('l'))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn transform(data: &str, to: &str) -> Result<String, Error> {
+        transform_long_table(
+            json!({
+                "data": data,
+                "arguments": {
+                    "caption": "Results",
+                    "label": "tab:results",
+                    "column-delimiter": "|",
+                    "row-delimiter": "\n",
+                    "scroll-height": "30em",
+                },
+            }),
+            to,
+        )
+    }
+
+    #[test]
+    fn parses_rows() {
+        let rows = parse_rows("a | b\n\n  \nc", "\n", "|").ok().unwrap();
+        assert_eq!(rows, [vec!["a", "b"], vec!["c"]]);
+        assert!(parse_rows("", "\n", "|").is_err());
+        assert!(parse_rows(" \n \n", "\n", "|").is_err());
+    }
+
+    #[test]
+    fn rejects_empty_tables() {
+        match transform("\n", "latex") {
+            Err(Error::LongTable(message)) => assert_eq!(message, "the table has no rows"),
+            _ => panic!("an empty table should be an error"),
+        }
+    }
+
+    #[test]
+    fn repeats_the_caption_on_following_pages() {
+        let latex = transform("Name | Score\nAda | 3", "latex").ok().unwrap();
+        let output: Vec<Value> = serde_json::from_str(&latex).unwrap();
+        let strings: Vec<&str> = output.iter().filter_map(Value::as_str).collect();
+
+        assert!(strings.contains(&"\\caption[]{"));
+        assert!(strings
+            .iter()
+            .any(|string| string.starts_with(" (continued)}")));
+        assert!(!strings
+            .iter()
+            .any(|string| string.contains("multicolumn{2}{l}")));
+    }
+
+    #[test]
+    fn validates_the_scroll_height() {
+        assert_eq!(css_length(" 30em "), Some("30em"));
+        assert_eq!(css_length("12.5vh"), Some("12.5vh"));
+        assert_eq!(css_length("50%"), Some("50%"));
+        assert_eq!(css_length("30"), None);
+        assert_eq!(css_length("0px"), None);
+        assert_eq!(css_length("em"), None);
+        assert_eq!(css_length("30em; color: red"), None);
+        assert_eq!(css_length("1.2.3em"), None);
+    }
+
+    #[test]
+    fn aligns_columns() {
+        assert_eq!(column_alignments("center", 2), ['c', 'c']);
+        assert_eq!(column_alignments("lr", 3), ['l', 'r', 'l']);
+        assert!(column_alignments("left", 0).is_empty());
+    }
+}
@@ -2,24 +2,79 @@
 
 use serde_json::{from_str, json, Value};
 use std::{
+    collections::HashMap,
     env,
     io::{self, Read},
+    time::{SystemTime, UNIX_EPOCH},
 };
 
+mod abstracts;
+mod author;
+mod bibliography;
+mod bibtex;
+mod chart;
+mod citation_style;
+mod contribution;
+mod csv;
+mod engine;
+mod escape;
+mod float;
+mod front_matter;
+mod gantt;
+mod glossary;
+mod how_to_cite;
 mod html_document;
+mod index;
+mod invocation;
 mod latex_document;
+mod long_table;
+mod metadata;
+mod page_layout;
+mod preamble;
+mod supervisor;
+mod svg;
+mod syntax_tree;
+use abstracts::{transform_abstract, Abstract};
+use author::Author;
+use bibliography::{transform_bibliography, transform_citation, transform_cite};
+use chart::transform_chart;
+use contribution::{transform_contribution, transform_written_by};
+use csv::{CsvTable, CSV_ARGUMENTS};
+use float::{
+    transform_figure_group, transform_float, transform_float_kind, transform_float_of,
+    transform_list_of, FloatKind,
+};
+use front_matter::Page;
+use gantt::transform_gantt;
+use glossary::{
+    transform_glossary, transform_glossary_def, transform_glossary_label, transform_glossary_use,
+};
 use html_document::transform_document_html;
+use index::{transform_index, transform_print_index};
+use invocation::ModuleInvocation;
 use latex_document::transform_document_latex;
+use long_table::{transform_long_table, LONG_TABLE_ARGUMENTS};
+use supervisor::Supervisor;
+use syntax_tree::transform_syntax_tree;
 
 enum Error {
     ConsumedInput,
     HeadingLevel(u8),
+    UnknownFloatKind(String),
+    Csv(String),
+    SyntaxTree(String),
+    Gantt(String),
+    Bibliography(String),
+    Svg(String),
+    Arguments(String),
+    FigureGroup(String),
+    LongTable(String),
 }
 
 fn main() {
     let args: Vec<String> = env::args().skip(1).collect();
 
-    let Some(action) = args.get(0) else {
+    let Some(action) = args.first() else {
         eprintln!("No action was provided.");
         return;
     };
@@ -45,6 +100,17 @@ fn handle_error(error: Error) {
             eprintln!("Invalid heading level '{level}'.")
         }
         Error::ConsumedInput => eprintln!("This module should not consume any input."),
+        Error::UnknownFloatKind(kind) => {
+            eprintln!("Unknown float kind '{kind}'. Declare it using [float-kind].")
+        }
+        Error::Csv(message) => eprintln!("{message}"),
+        Error::SyntaxTree(message) => eprintln!("Invalid syntax tree: {message}"),
+        Error::Gantt(message) => eprintln!("Invalid Gantt chart: {message}"),
+        Error::Bibliography(message) => eprintln!("{message}"),
+        Error::Svg(message) => eprintln!("{message}"),
+        Error::Arguments(message) => eprintln!("{message}"),
+        Error::FigureGroup(message) => eprintln!("Invalid figure group: {message}"),
+        Error::LongTable(message) => eprintln!("Invalid long table: {message}"),
     }
 }
 
@@ -66,107 +132,68 @@ fn transform(from: &str, to: &str) -> Result<String, Error> {
         "note-label" => transform_note_label(input, to),
         "label" => transform_label(input, to),
         "reference" => transform_reference(input, to),
-        "fancy-image" => transform_fancy_image(input, to),
+        "fancy-image" => transform_fancy("figure", input, to),
         "fancy-table" => transform_fancy_table(input, to),
         "fancy-big-table" => transform_fancy_big_table(input, to),
+        "float" => transform_float(input, to),
+        "float-kind" => transform_float_kind(input, to),
+        "list-of" => transform_list_of(input, to),
+        "figure-group" => transform_figure_group(input, to),
+        "chart" => transform_chart(input, to),
+        "fancy-chart" => transform_fancy("chart", input, to),
+        "syntax-tree" => transform_syntax_tree(input, to),
+        "fancy-syntax-tree" => transform_fancy("syntax-tree", input, to),
+        "gantt" => transform_gantt(input, to),
+        "abstract" => transform_abstract(input, to),
+        "acronym-def" => transform_glossary_def(true, input),
+        "gloss-def" => transform_glossary_def(false, input),
+        "acr" | "gls" => transform_glossary_use(input),
+        "glossary-use" => transform_glossary_label(input, to),
+        "glossary" => transform_glossary(input, to),
+        "cite" => transform_cite(input, to),
+        "citation" => transform_citation(input, to),
+        "bibliography" => transform_bibliography(input, to),
+        "index" => transform_index(input, to),
+        "print-index" => transform_print_index(input, to),
+        "contribution" => transform_contribution(input, to),
+        "written-by" => transform_written_by(input, to),
+        "fancy-gantt" => transform_fancy("gantt", input, to),
         "element-number" => transform_element_number(input, to),
         _ => panic!("element not supported"),
     }
 }
 
-fn transform_fancy_image(input: Value, to: &str) -> Result<String, Error> {
-    let data = input["data"].as_str().unwrap();
-
-    let alt = input["arguments"]["alt"].as_str().unwrap();
-    let width = input["arguments"]["width"].as_f64().unwrap().to_string();
-    let mut caption = input["arguments"]["caption"].as_str().unwrap().to_string();
-    let cap_align = input["arguments"]["caption-alignment"].as_str().unwrap();
-    let label = input["arguments"]["label"].as_str().unwrap();
-    let embed = input["arguments"]["embed"].as_str().unwrap();
-
-    // making use of the fact that caption becomes inline-content in [image]
-    if to == "html" {
-        caption = format!("**Figure [element-number]({label}):** {caption}");
-    }
-
-    let module_invoc = format!(
-        "[image \"{}\" \"{}\" \"{}\" \"{}\" \"{}\" \"{}\"](((\n{}\n)))",
-        alt, caption, label, width, embed, cap_align, data,
-    );
-
-    let label_entry = format!("label/{}", label);
-    let json = json!([
-        {"name": "list-push", "arguments": {"name": "structure"}, "data": "fig"},
-        {"name": "list-push", "arguments": {"name": "structure"}, "data": label_entry},
-        {"name": "block_content", "data": module_invoc},
-    ]);
-
-    Ok(serde_json::to_string(&json).unwrap())
+/// The fancy modules are floats of a built in kind, see [FloatKind::builtins]
+fn transform_fancy(kind: &str, input: Value, to: &str) -> Result<String, Error> {
+    let kind = FloatKind::find(kind).unwrap();
+    transform_float_of(&kind, input, to)
 }
 
-fn transform_fancy_table(input: Value, to: &str) -> Result<String, Error> {
-    let data = input["data"].as_str().unwrap();
-
-    let mut caption = input["arguments"]["caption"].as_str().unwrap().to_string();
-    let label = input["arguments"]["label"].as_str().unwrap();
-    let header = input["arguments"]["header"].as_str().unwrap();
-    let alignment = input["arguments"]["alignment"].as_str().unwrap();
-    let borders = input["arguments"]["borders"].as_str().unwrap();
-    let delimiter = input["arguments"]["delimiter"].as_str().unwrap();
-    let strip = input["arguments"]["strip_whitespace"].as_str().unwrap();
-
-    // making use of the fact that caption becomes inline-content in [table]
-    if to == "html" {
-        caption = format!("**Table [element-number]({label}):** {caption}");
+fn transform_fancy_table(mut input: Value, to: &str) -> Result<String, Error> {
+    let data = input["data"].as_str().unwrap_or_default();
+    if let Some(table) = CsvTable::from_arguments(data, &input["arguments"])? {
+        let (content, delimiter) = table.to_table_content();
+        input["data"] = json!(content);
+        input["arguments"]["delimiter"] = json!(delimiter);
     }
 
-    let module_invoc = format!(
-        "[table \"{}\" \"{}\" \"{}\" \"{}\" \"{}\" \"{}\" \"{}\"](((\n{}\n)))",
-        caption, label, header, alignment, borders, delimiter, strip, data,
-    );
-
-    let label_entry = format!("label/{}", label);
-    let json = json!([
-        {"name": "list-push", "arguments": {"name": "structure"}, "data": "tab"},
-        {"name": "list-push", "arguments": {"name": "structure"}, "data": label_entry},
-        {"name": "block_content", "data": module_invoc},
-    ]);
-
-    Ok(serde_json::to_string(&json).unwrap())
+    // std [table] doesn't know about the CSV arguments
+    if let Some(arguments) = input["arguments"].as_object_mut() {
+        arguments.retain(|name, _| !CSV_ARGUMENTS.contains(&name.as_str()));
+    }
+    transform_fancy("table", input, to)
 }
 
-fn transform_fancy_big_table(input: Value, to: &str) -> Result<String, Error> {
-    let data = input["data"].as_str().unwrap();
-
-    let mut caption = input["arguments"]["caption"].as_str().unwrap().to_string();
-    let label = input["arguments"]["label"].as_str().unwrap();
-    let alignment = input["arguments"]["alignment"].as_str().unwrap();
-
-    let borders = input["arguments"]["borders"].as_str().unwrap();
-    let col_delimiter = input["arguments"]["column-delimiter"].as_str().unwrap();
-
-    let row_delimiter = input["arguments"]["row-delimiter"]
-        .as_str()
-        .unwrap()
-        .to_string();
-
-    if to == "html" {
-        caption = format!("**Table [element-number]({label}):** {caption}");
+fn transform_fancy_big_table(mut input: Value, to: &str) -> Result<String, Error> {
+    if input["arguments"]["long"].as_str() == Some("true") {
+        return transform_long_table(input, to);
     }
 
-    let module_invoc = format!(
-        "[big-table \"{}\" \"{}\" \"{}\" \"{}\" \"{}\" \"{}\"](((\n{}\n)))",
-        caption, label, alignment, borders, col_delimiter, row_delimiter, data,
-    );
-
-    let label_entry = format!("label/{}", label);
-    let json = json!([
-        {"name": "list-push", "arguments": {"name": "structure"}, "data": "tab"},
-        {"name": "list-push", "arguments": {"name": "structure"}, "data": label_entry},
-        {"name": "block_content", "data": module_invoc},
-    ]);
-
-    Ok(serde_json::to_string(&json).unwrap())
+    // std [big-table] doesn't know about the long table arguments
+    if let Some(arguments) = input["arguments"].as_object_mut() {
+        arguments.retain(|name, _| !LONG_TABLE_ARGUMENTS.contains(&name.as_str()));
+    }
+    transform_fancy("big-table", input, to)
 }
 
 fn transform_label(input: Value, to: &str) -> Result<String, Error> {
@@ -240,14 +267,14 @@ fn transform_heading(heading: Value, to: &str) -> Result<String, Error> {
             list.push(json!({"name": "list-push", "arguments": {"name": "structure"}, "data": dummy_label}));
 
             if level == 1 {
-                list.push(Value::String(format!("<div class=big-number>")));
+                list.push(Value::String("<div class=big-number>".to_string()));
                 list.push(json!({"name": "inline_content", "data": elem_num_invoc}));
-                list.push(Value::String(format!("</div>")));
+                list.push(Value::String("</div>".to_string()));
                 list.push(Value::String(format!("<h{level}>")));
             } else if level < 5 {
                 list.push(Value::String(format!("<h{level}>")));
                 list.push(json!({"name": "inline_content", "data": elem_num_invoc}));
-                list.push(Value::String(format!(" ")));
+                list.push(Value::String(" ".to_string()));
             } else {
                 list.push(Value::String(format!("<h{level}>")));
             }
@@ -304,11 +331,18 @@ fn transform_reference(input: Value, to: &str) -> Result<String, Error> {
 
 fn transform_element_number(input: Value, _to: &str) -> Result<String, Error> {
     let label = input["data"].as_str().unwrap();
-    let mut fig_count = 0;
-    let mut tab_count = 0;
-    let mut sec_counts = vec![0; 5];
+    // floats are counted per counter, see FloatKind::counter
+    let mut float_counts: HashMap<&str, usize> = HashMap::new();
+    // subfigures are counted within the float that precedes them
+    let mut sub_count = 0;
+    let mut parent = "h";
+    let mut sec_counts = [0; 5];
     let mut prev = "h";
     let mut number = String::new();
+    let counters: Vec<String> = FloatKind::all()
+        .into_iter()
+        .map(|kind| kind.counter)
+        .collect();
 
     let structure: Vec<String> = {
         let var = env::var("structure").unwrap_or("[]".to_string());
@@ -317,42 +351,53 @@ fn transform_element_number(input: Value, _to: &str) -> Result<String, Error> {
 
     for item in &structure {
         match item.as_str() {
-            "fig" => {
-                fig_count += 1;
-                prev = "fig";
-            }
-            "tab" => {
-                tab_count += 1;
-                prev = "tab";
-            }
             "h1" | "h2" | "h3" | "h4" | "h5" => {
                 let level = item[1..].parse::<usize>().unwrap();
-                for i in level..sec_counts.len() {
-                    sec_counts[i] = 0;
+                for count in sec_counts.iter_mut().skip(level) {
+                    *count = 0;
                 }
                 sec_counts[level - 1] += 1;
                 if item == "h1" {
-                    fig_count = 0;
-                    tab_count = 0;
+                    float_counts.clear();
                 }
 
                 prev = "h";
             }
             _ => {
-                if Some(label) == item.strip_prefix("label/") {
-                    number = match prev {
-                        "fig" => format!("{}.{}", sec_counts[0], fig_count),
-                        "tab" => format!("{}.{}", sec_counts[0], tab_count),
-                        "h" => sec_counts
-                            .iter()
-                            .filter(|&&c| c != 0)
-                            .map(|c| c.to_string())
-                            .collect::<Vec<String>>()
-                            .join("."),
-                        _ => String::new(),
-                    };
-                    break;
+                if let Some(item_label) = item.strip_prefix("label/") {
+                    if label == item_label {
+                        number = match prev {
+                            "h" => sec_counts
+                                .iter()
+                                .filter(|&&c| c != 0)
+                                .map(|c| c.to_string())
+                                .collect::<Vec<String>>()
+                                .join("."),
+                            "subfig" => format!(
+                                "{}.{}{}",
+                                sec_counts[0],
+                                float_counts.get(parent).unwrap_or(&0),
+                                subfigure_letter(sub_count)
+                            ),
+                            counter => format!(
+                                "{}.{}",
+                                sec_counts[0],
+                                float_counts.get(counter).unwrap_or(&0)
+                            ),
+                        };
+                        break;
+                    }
+                } else if item == "subfig" {
+                    sub_count += 1;
+                    prev = "subfig";
+                } else if counters.contains(item) {
+                    // "fig", "tab" and "float/..." entries
+                    *float_counts.entry(item.as_str()).or_default() += 1;
+                    sub_count = 0;
+                    parent = item.as_str();
+                    prev = item.as_str();
                 }
+                // anything else, such as "h6", isn't numbered
             }
         }
     }
@@ -361,6 +406,11 @@ fn transform_element_number(input: Value, _to: &str) -> Result<String, Error> {
     Ok(format!("[{json}]"))
 }
 
+/// The letter of a subfigure, 1 => "a", 2 => "b" and so on
+fn subfigure_letter(count: usize) -> char {
+    (b'a' + (count.saturating_sub(1) % 26) as u8) as char
+}
+
 fn transform_note(input: Value, to: &str) -> Result<String, Error> {
     let result = match to {
         // It's very easy when using latex, just add a \footnote{...}
@@ -463,26 +513,16 @@ fn transform_latex_command(command: &str, input: Value, to: &str) -> Result<Stri
 }
 
 struct DocSettings {
-    /// Name of authors
-    authors: Vec<String>,
+    /// The authors, with any details given about them
+    authors: Vec<Author>,
     /// Title of thesis
     title: Option<String>,
     /// Subtitle of thesis
     subtitle: Option<String>,
     /// Department the authors belong to
     department: Option<String>,
-    /// Name of the supervisor
-    supervisor: Option<String>,
-    /// The supervisor's department
-    supervisor_department: Option<String>,
-    /// Name of the (groups) examiner
-    examiner: Option<String>,
-    /// Dartment of the (groups) examiner
-    examiner_department: Option<String>,
-    /// Name of course examiner(s)
-    course_examiner: Option<String>,
-    /// Department of the course examiner
-    course_examiner_department: Option<String>,
+    /// Supervisors and examiners, ordered by their role
+    supervisors: Vec<Supervisor>,
     /// Path to the cover art
     cover_art: Option<String>,
     /// A description of the cover art
@@ -493,11 +533,31 @@ struct DocSettings {
     sammandrag: Option<String>,
     /// Keywords that are mentioned in the abstract
     keywords: Option<String>,
+    /// Abstracts added using `[abstract]`, such as a popular science summary
+    abstracts: Vec<Abstract>,
     /// The acknowledgement text
     acknowledgements_content: Option<String>,
     /// Used on the titlepage
     /// For instance, "Computer Science and Engineering".
     subject: Option<String>,
+    /// Where the contribution report goes: "front-matter", "appendix" or "none"
+    contribution_report: String,
+    /// A short dedication on a page of its own
+    dedication: Option<String>,
+    /// A quotation on a page of its own
+    epigraph: Option<String>,
+    /// Who the epigraph is by
+    epigraph_attribution: Option<String>,
+    /// The preface text
+    preface: Option<String>,
+    /// Abbreviations written one per line as "ABBR: expansion"
+    abbreviations: Option<String>,
+    /// The pages of the front matter, in order
+    front_matter: Vec<Page>,
+    /// The year the thesis is published, if it isn't the current one
+    year: Option<String>,
+    /// The PDF/A level, such as "2b", for a PDF that can be archived
+    pdf_a: Option<String>,
 }
 
 impl DocSettings {
@@ -512,19 +572,25 @@ impl DocSettings {
             title: Self::read_const("title"),
             subtitle: Self::read_const("subtitle"),
             department: Self::read_const("department"),
-            supervisor: Self::read_const("supervisor"),
-            supervisor_department: Self::read_const("supervisor_department"),
-            examiner: Self::read_const("examiner"),
-            examiner_department: Self::read_const("examiner_department"),
+            supervisors: Supervisor::read_all(),
             cover_art: Self::read_const("cover_art"),
             cover_art_description: Self::read_const("cover_art_description"),
             abstract_content: Self::read_const("abstract"),
             sammandrag: Self::read_const("sammandrag"),
             acknowledgements_content: Self::read_const("acknowledgements"),
-            course_examiner: Self::read_const("course_examiner"),
-            course_examiner_department: Self::read_const("course_examiner_department"),
             subject: Self::read_const("subject"),
             keywords: Self::read_const("keywords"),
+            abstracts: Abstract::read_all(),
+            contribution_report: Self::read_optional_const("contribution_report")
+                .unwrap_or_else(|| "front-matter".to_string()),
+            dedication: Self::read_optional_const("dedication"),
+            epigraph: Self::read_optional_const("epigraph"),
+            epigraph_attribution: Self::read_optional_const("epigraph_attribution"),
+            preface: Self::read_optional_const("preface"),
+            abbreviations: Self::read_optional_const("abbreviations"),
+            front_matter: Page::from_list(Self::read_optional_const("front_matter").as_deref()),
+            year: Self::read_optional_const("year"),
+            pdf_a: Self::read_optional_const("pdf_a"),
         }
     }
 
@@ -539,26 +605,41 @@ impl DocSettings {
         value
     }
 
+    /// Get a constant from a environment variable, for constants that most
+    /// documents leave out
+    fn read_optional_const(name: &str) -> Option<String> {
+        env::var(name).ok()
+    }
+
     /// Get a list of all authors
-    fn read_authors() -> Vec<String> {
+    fn read_authors() -> Vec<Author> {
         let Ok(variable) = env::var("authors") else {
-                return Vec::new();
-            };
+            return Vec::new();
+        };
 
         let Ok(Value::Array(array)) = serde_json::from_str(&variable) else {
-                unreachable!("authors is of the type list");
+            unreachable!("authors is of the type list");
         };
 
         array
             .into_iter()
             .map(|s| {
                 if let Value::String(s) = s {
-                    s
+                    Author::parse(&s)
                 } else {
                     unreachable!("ModMark lists always contain strings")
                 }
             })
-            .collect::<Vec<String>>()
+            .collect::<Vec<Author>>()
+    }
+
+    /// The names of all authors, separated by commas
+    fn author_names(&self) -> String {
+        self.authors
+            .iter()
+            .map(|author| author.name.as_str())
+            .collect::<Vec<_>>()
+            .join(", ")
     }
 
     fn get_title(&self) -> String {
@@ -567,6 +648,12 @@ impl DocSettings {
             .unwrap_or_else(|| "Missing title".to_string())
     }
 
+    fn get_year(&self) -> String {
+        self.year
+            .clone()
+            .unwrap_or_else(|| current_year().to_string())
+    }
+
     fn get_notes() -> Vec<Value> {
         let var = env::var("notes").unwrap_or_else(|_| "[]".to_string());
         let array: Value = serde_json::from_str(&var).unwrap();
@@ -574,12 +661,30 @@ impl DocSettings {
         array
             .as_array()
             .unwrap()
-            .into_iter()
+            .iter()
             .map(|note| serde_json::from_str(note.as_str().unwrap()).unwrap())
             .collect()
     }
 }
 
+/// The current year, computed from the days since 1970 like Howard
+/// Hinnant's `civil_from_days`
+fn current_year() -> i64 {
+    let days = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |time| time.as_secs() / 86400) as i64;
+
+    let days = days + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days - era * 146097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    // the year starts in March, so January and February belong to the next one
+    let month = (5 * day_of_year + 2) / 153;
+    year_of_era + era * 400 + i64::from(month >= 10)
+}
+
 fn transform_document(input: Value, to: &str) -> Result<String, Error> {
     match to {
         "html" => transform_document_html(input),
@@ -631,18 +736,40 @@ fn manifest() -> String {
                         "examiner_department": {"type": "const", "access": "read"},
                         "course_examiner": {"type": "const", "access": "read"},
                         "course_examiner_department": {"type": "const", "access": "read"},
+                        "supervisors": {"type": "list", "access": "read"},
+                        "examiners": {"type": "list", "access": "read"},
                         "cover_art": {"type": "const", "access": "read"},
                         "cover_art_description": {"type": "const", "access": "read"},
                         "abstract": {"type": "const", "access": "read"},
                         "acknowledgements": {"type": "const", "access": "read"},
                         "language": {"type": "const", "access": "read"},
                         "sources": {"type": "const", "access": "read"},
+                        "citation_style": {"type": "const", "access": "read"},
                         "subject": {"type": "const", "access": "read"},
                         "keywords": {"type": "const", "access": "read"},
                         "sammandrag": {"type": "const", "access": "read"},
+                        "abstracts": {"type": "list", "access": "read"},
                         "notes": {"type": "list", "access": "read"},
+                        "contributions": {"type": "list", "access": "read"},
+                        "contribution_report": {"type": "const", "access": "read"},
+                        "dedication": {"type": "const", "access": "read"},
+                        "epigraph": {"type": "const", "access": "read"},
+                        "epigraph_attribution": {"type": "const", "access": "read"},
+                        "preface": {"type": "const", "access": "read"},
+                        "abbreviations": {"type": "const", "access": "read"},
+                        "front_matter": {"type": "const", "access": "read"},
+                        "year": {"type": "const", "access": "read"},
+                        "pdf_a": {"type": "const", "access": "read"},
+                        "engine": {"type": "const", "access": "read"},
+                        "main_font": {"type": "const", "access": "read"},
+                        "sans_font": {"type": "const", "access": "read"},
+                        "mono_font": {"type": "const", "access": "read"},
+                        "language_package": {"type": "const", "access": "read"},
+                        "layout": {"type": "const", "access": "read"},
+                        "glossary": {"type": "list", "access": "read"},
                     },
-                    "type": "parent"
+                    "type": "parent",
+                    "description": "The thesis template. The constants title, subtitle, subject and keywords are written in LaTeX, both on the title pages and in the metadata of the PDF, where hyperref turns them into plain text. Use \\texorpdfstring for LaTeX that has no plain text form, such as math."
                 },
                 {
                     "from": "__heading",
@@ -712,11 +839,16 @@ fn manifest() -> String {
                         {
                             "name": "caption-alignment", "default": "center", "type": ["left", "center", "right"], "description": "The alignment of the image caption."
                         },
+                        {"name": "full-width", "default": "false", "type": ["true", "false"], "description": "Lets the float extend into the margins."},
+                        {"name": "landscape", "default": "false", "type": ["true", "false"], "description": "Rotates the float onto a landscape page in LaTeX."},
                     ],
                     "variables": {
                         "structure": {"type": "list", "access": "push"},
+                        "floats": {"type": "list", "access": "push"},
+                        "float-kinds": {"type": "list", "access": "read"},
                         "imports": {"type": "set", "access": "add"}
-                    }
+                    },
+                    "description": "Includes an image as a numbered figure. SVG images are drawn using PGF in LaTeX, so that no shell escape or Inkscape is needed."
                 },
                 {
                     "from": "fancy-table",
@@ -728,8 +860,22 @@ fn manifest() -> String {
                         {"name": "alignment", "default": "left", "description": "Horizontal alignment in cells, left/center/right or l/c/r for each column"},
                         {"name": "borders", "default": "all", "type": ["all", "horizontal", "vertical", "outer", "none"], "description": "Which borders to draw"},
                         {"name": "delimiter", "default": "|", "description": "The delimiter between cells"},
-                        {"name": "strip_whitespace", "default": "true", "type": ["true", "false"], "description": "true/false to strip/don't strip whitespace in cells"}
+                        {"name": "strip_whitespace", "default": "true", "type": ["true", "false"], "description": "true/false to strip/don't strip whitespace in cells"},
+                        {"name": "file", "default": "", "description": "Path to a CSV or TSV file to read the table from, instead of the content of the module. The first row is the header."},
+                        {"name": "format", "default": "auto", "type": ["auto", "csv", "tsv"], "description": "How the table is read. 'auto' uses the file extension of 'file', or the delimiter for inline content. 'csv' and 'tsv' also apply to inline content."},
+                        {"name": "columns", "default": "", "description": "Comma separated names or 1-based indices of the columns to show, in the order to show them in. Shows all columns if empty."},
+                        {"name": "decimals", "default": -1, "type": "int", "description": "Rounds the numbers in the columns given by number-columns to this many decimals. Keeps numbers as they are if negative."},
+                        {"name": "number-columns", "default": "", "description": "Comma separated names or 1-based indices of the columns that decimals applies to."},
+                        {"name": "sort", "default": "", "description": "Name or index of the column to sort the rows by, which does not have to be among the shown columns. Prefix with '-' to sort in descending order."},
+                        {"name": "limit", "default": 0, "type": "uint", "description": "The maximum number of rows to show, not counting the header. Shows all rows if 0."},
+                        {"name": "full-width", "default": "false", "type": ["true", "false"], "description": "Lets the float extend into the margins."},
+                        {"name": "landscape", "default": "false", "type": ["true", "false"], "description": "Rotates the float onto a landscape page in LaTeX."},
                     ],
+                    "variables": {
+                        "structure": {"type": "list", "access": "push"},
+                        "floats": {"type": "list", "access": "push"},
+                        "float-kinds": {"type": "list", "access": "read"},
+                    },
                     "unknown-content": true,
                     "description": "Makes a table. Use one row for each row in the table, and separate the columns by the delimiter (default = |)"
                 },
@@ -743,10 +889,374 @@ fn manifest() -> String {
                         {"name": "borders", "default": "all", "type": ["all", "horizontal", "vertical", "outer", "none"], "description": "Which borders to draw"},
                         {"name": "column-delimiter", "default": "[next-column]", "description": "The delimiter between columns"},
                         {"name": "row-delimiter", "default": "[next-row]", "description": "The delimiter between rows"},
+                        {"name": "long", "default": "false", "type": ["true", "false"], "description": "Lets the table span several pages, repeating the header rows on every page. In HTML, the header sticks to the top while scrolling."},
+                        {"name": "header-rows", "default": 1, "type": "uint", "description": "The number of rows that make up the header of a long table."},
+                        {"name": "scroll-height", "default": "", "description": "Puts a long table in a scroll container of this height in HTML. It is a CSS length such as '30em', '20vh' or '400px'."},
+                        {"name": "full-width", "default": "false", "type": ["true", "false"], "description": "Lets the float extend into the margins."},
+                        {"name": "landscape", "default": "false", "type": ["true", "false"], "description": "Rotates the float onto a landscape page in LaTeX."},
                     ],
+                    "variables": {
+                        "structure": {"type": "list", "access": "push"},
+                        "floats": {"type": "list", "access": "push"},
+                        "float-kinds": {"type": "list", "access": "read"},
+                        "imports": {"type": "set", "access": "add"},
+                    },
                     "unknown-content": true,
                     "description": "Large variant of the table, which accepts block content. Write the content of each cell on multiple lines, and use column-delimiter between cells on the same row. Then, use row-delimiter between rows."
                 },
+                {
+                    "from": "float",
+                    "to": ["html", "latex"],
+                    "type": "multiline-module",
+                    "arguments": [
+                        {"name": "kind", "description": "The kind of float, such as figure, table, listing or one declared using [float-kind]"},
+                        {"name": "caption", "default": "", "description": "The caption of the float"},
+                        {"name": "label", "default": "", "description": "The label to use for the float, to be able to refer to it from the document"},
+                        {"name": "args", "default": "", "description": "Arguments passed on to the wrapped module, such as 'lang=rust' for listings"},
+                        {"name": "placement", "default": "here", "type": ["here", "top", "bottom", "page", "auto"], "description": "Where LaTeX places the float: where it is written, at the top or bottom of a page, on a page of its own or wherever it fits best. A full width float is always placed where it is written, and so are figures and tables, since std [image] and [table] render them."},
+                        {"name": "full-width", "default": "false", "type": ["true", "false"], "description": "Lets the float extend into the margins."},
+                        {"name": "landscape", "default": "false", "type": ["true", "false"], "description": "Rotates the float onto a landscape page in LaTeX."},
+                    ],
+                    "variables": {
+                        "structure": {"type": "list", "access": "push"},
+                        "floats": {"type": "list", "access": "push"},
+                        "float-kinds": {"type": "list", "access": "read"},
+                        "imports": {"type": "set", "access": "add"}
+                    },
+                    "unknown-content": true,
+                    "description": "Wraps a module in a numbered float with a caption and a label. For example, [float listing caption=\"Hello world\" args=\"lang=rust\"] wraps [code] in a listing."
+                },
+                {
+                    "from": "figure-group",
+                    "to": ["html", "latex"],
+                    "type": "multiline-module",
+                    "arguments": [
+                        {"name": "caption", "default": "", "description": "The caption shared by all subfigures"},
+                        {"name": "label", "default": "", "description": "The label to use for the whole figure, to be able to refer to it from the document"},
+                        {"name": "columns", "default": 2, "type": "uint", "description": "The number of subfigures on each row"},
+                        {"name": "delimiter", "default": "|", "description": "The delimiter between the path, caption and label of a subfigure"},
+                        {"name": "placement", "default": "here", "type": ["here", "top", "bottom", "page", "auto"], "description": "Where LaTeX places the float: where it is written, at the top or bottom of a page, on a page of its own or wherever it fits best. A full width float is always placed where it is written."},
+                        {"name": "full-width", "default": "false", "type": ["true", "false"], "description": "Lets the float extend into the margins."},
+                        {"name": "landscape", "default": "false", "type": ["true", "false"], "description": "Rotates the float onto a landscape page in LaTeX."},
+                    ],
+                    "variables": {
+                        "structure": {"type": "list", "access": "push"},
+                        "floats": {"type": "list", "access": "push"},
+                        "float-kinds": {"type": "list", "access": "read"},
+                        "imports": {"type": "set", "access": "add"}
+                    },
+                    "unknown-content": true,
+                    "description": "A figure made up of at most 26 subfigures labelled (a), (b), (c) and so on. Write one subfigure per line as 'path | caption | label', where the caption and label are optional. Referencing a subfigure gives numbers such as 5.3b."
+                },
+                {
+                    "from": "chart",
+                    "to": ["html", "latex"],
+                    "type": "multiline-module",
+                    "arguments": [
+                        {"name": "type", "default": "bar", "type": ["bar", "grouped-bar", "line", "scatter"], "description": "The type of chart. A bar chart shows a single series, a grouped bar chart shows all series next to each other. The first column of a scatter plot holds numbers."},
+                        {"name": "x-label", "default": "", "description": "The label of the x axis"},
+                        {"name": "y-label", "default": "", "description": "The label of the y axis"},
+                        {"name": "legend", "default": "true", "type": ["true", "false"], "description": "Whether to show a legend of the series"},
+                        {"name": "width", "default": 600.0, "type": "f64", "description": "The width of the chart in pixels, the chart is scaled to the width of the text in LaTeX"},
+                        {"name": "height", "default": 360.0, "type": "f64", "description": "The height of the chart in pixels"},
+                        {"name": "file", "default": "", "description": "Path to a CSV or TSV file to read the data from, instead of the content of the module"},
+                        {"name": "format", "default": "csv", "type": ["auto", "csv", "tsv"], "description": "How the data is read"},
+                        {"name": "columns", "default": "", "description": "Comma separated names or 1-based indices of the columns to use, starting with the categories"},
+                    ],
+                    "unknown-content": true,
+                    "description": "Draws a chart from CSV data, where the first column holds the categories (or x values) and every other column is a series. The chart is an SVG image, which is drawn using PGF in LaTeX."
+                },
+                {
+                    "from": "fancy-chart",
+                    "to": ["html", "latex"],
+                    "type": "multiline-module",
+                    "arguments": [
+                        {"name": "caption", "default": "", "description": "The caption for the chart"},
+                        {"name": "label", "default": "", "description": "The label to use for the chart, to be able to refer to it from the document"},
+                        {"name": "type", "default": "bar", "type": ["bar", "grouped-bar", "line", "scatter"], "description": "The type of chart. A bar chart shows a single series, a grouped bar chart shows all series next to each other. The first column of a scatter plot holds numbers."},
+                        {"name": "x-label", "default": "", "description": "The label of the x axis"},
+                        {"name": "y-label", "default": "", "description": "The label of the y axis"},
+                        {"name": "legend", "default": "true", "type": ["true", "false"], "description": "Whether to show a legend of the series"},
+                        {"name": "width", "default": 600.0, "type": "f64", "description": "The width of the chart in pixels, the chart is scaled to the width of the text in LaTeX"},
+                        {"name": "height", "default": 360.0, "type": "f64", "description": "The height of the chart in pixels"},
+                        {"name": "file", "default": "", "description": "Path to a CSV or TSV file to read the data from, instead of the content of the module"},
+                        {"name": "format", "default": "csv", "type": ["auto", "csv", "tsv"], "description": "How the data is read"},
+                        {"name": "columns", "default": "", "description": "Comma separated names or 1-based indices of the columns to use, starting with the categories"},
+                        {"name": "placement", "default": "here", "type": ["here", "top", "bottom", "page", "auto"], "description": "Where LaTeX places the float: where it is written, at the top or bottom of a page, on a page of its own or wherever it fits best. A full width float is always placed where it is written."},
+                        {"name": "full-width", "default": "false", "type": ["true", "false"], "description": "Lets the float extend into the margins."},
+                        {"name": "landscape", "default": "false", "type": ["true", "false"], "description": "Rotates the float onto a landscape page in LaTeX."},
+                    ],
+                    "variables": {
+                        "structure": {"type": "list", "access": "push"},
+                        "floats": {"type": "list", "access": "push"},
+                        "float-kinds": {"type": "list", "access": "read"},
+                    },
+                    "unknown-content": true,
+                    "description": "A chart in a numbered figure, see [chart]."
+                },
+                {
+                    "from": "syntax-tree",
+                    "to": ["html", "latex"],
+                    "type": "multiline-module",
+                    "arguments": [],
+                    "variables": {
+                        "imports": {"type": "set", "access": "add"}
+                    },
+                    "unknown-content": true,
+                    "description": "Draws a tree from labelled bracket notation, such as [Document [Paragraph [Text \"hi\"]]]. Quoted leaves are drawn as plain text. Uses the forest package in LaTeX. Use [fancy-syntax-tree] for a numbered figure."
+                },
+                {
+                    "from": "fancy-syntax-tree",
+                    "to": ["html", "latex"],
+                    "type": "multiline-module",
+                    "arguments": [
+                        {"name": "caption", "default": "", "description": "The caption for the tree"},
+                        {"name": "label", "default": "", "description": "The label to use for the tree, to be able to refer to it from the document"},
+                        {"name": "placement", "default": "here", "type": ["here", "top", "bottom", "page", "auto"], "description": "Where LaTeX places the float: where it is written, at the top or bottom of a page, on a page of its own or wherever it fits best. A full width float is always placed where it is written."},
+                        {"name": "full-width", "default": "false", "type": ["true", "false"], "description": "Lets the float extend into the margins."},
+                        {"name": "landscape", "default": "false", "type": ["true", "false"], "description": "Rotates the float onto a landscape page in LaTeX."},
+                    ],
+                    "variables": {
+                        "structure": {"type": "list", "access": "push"},
+                        "floats": {"type": "list", "access": "push"},
+                        "float-kinds": {"type": "list", "access": "read"},
+                        "imports": {"type": "set", "access": "add"}
+                    },
+                    "unknown-content": true,
+                    "description": "A syntax tree in a numbered figure, see [syntax-tree]."
+                },
+                {
+                    "from": "gantt",
+                    "to": ["html", "latex"],
+                    "type": "multiline-module",
+                    "arguments": [
+                        {"name": "week-label", "default": "Week", "description": "The label above the task names, in front of the week numbers"},
+                        {"name": "width", "default": 640.0, "type": "f64", "description": "The width of the chart in pixels in HTML, the chart fills the width of the text in LaTeX"},
+                        {"name": "file", "default": "", "description": "Path to a file to read the tasks from, instead of the content of the module. This lets the planning report and the thesis share the same time plan."},
+                    ],
+                    "variables": {
+                        "imports": {"type": "set", "access": "add"}
+                    },
+                    "unknown-content": true,
+                    "description": "Draws a time plan as a Gantt chart. Write one task per line as 'name | start-end | owners', where the weeks are inclusive and the owners are comma separated, and milestones as 'milestone: name | week'. Lines starting with # are ignored. Uses TikZ in LaTeX and an SVG image in HTML."
+                },
+                {
+                    "from": "fancy-gantt",
+                    "to": ["html", "latex"],
+                    "type": "multiline-module",
+                    "arguments": [
+                        {"name": "caption", "default": "", "description": "The caption for the time plan"},
+                        {"name": "label", "default": "", "description": "The label to use for the time plan, to be able to refer to it from the document"},
+                        {"name": "week-label", "default": "Week", "description": "The label above the task names, in front of the week numbers"},
+                        {"name": "width", "default": 640.0, "type": "f64", "description": "The width of the chart in pixels in HTML, the chart fills the width of the text in LaTeX"},
+                        {"name": "file", "default": "", "description": "Path to a file to read the tasks from, instead of the content of the module. This lets the planning report and the thesis share the same time plan."},
+                        {"name": "placement", "default": "here", "type": ["here", "top", "bottom", "page", "auto"], "description": "Where LaTeX places the float: where it is written, at the top or bottom of a page, on a page of its own or wherever it fits best. A full width float is always placed where it is written."},
+                        {"name": "full-width", "default": "false", "type": ["true", "false"], "description": "Lets the float extend into the margins."},
+                        {"name": "landscape", "default": "false", "type": ["true", "false"], "description": "Rotates the float onto a landscape page in LaTeX."},
+                    ],
+                    "variables": {
+                        "structure": {"type": "list", "access": "push"},
+                        "floats": {"type": "list", "access": "push"},
+                        "float-kinds": {"type": "list", "access": "read"},
+                        "imports": {"type": "set", "access": "add"}
+                    },
+                    "unknown-content": true,
+                    "description": "A Gantt chart in a numbered figure, see [gantt]."
+                },
+                {
+                    "from": "abstract",
+                    "to": ["any"],
+                    "arguments": [
+                        {"name": "language", "default": "en", "description": "The language of the abstract as a language tag, such as 'sv' or 'de'. It decides the hyphenation rules in LaTeX and the lang attribute in HTML."},
+                        {"name": "heading", "default": "Abstract", "description": "The heading of the abstract, for instance 'Populärvetenskaplig sammanfattning'"},
+                        {"name": "keywords", "default": "", "description": "Keywords shown below the abstract"},
+                    ],
+                    "variables": {
+                        "abstracts": {"type": "list", "access": "push"}
+                    },
+                    "unknown-content": true,
+                    "description": "Adds an abstract to the front matter, after the English abstract and the Swedish sammandrag. Use it for a popular science summary or an abstract in another language. Each abstract gets a page of its own in LaTeX."
+                },
+                {
+                    "from": "acronym-def",
+                    "to": ["any"],
+                    "arguments": [
+                        {"name": "key", "description": "The acronym, such as WASI"},
+                    ],
+                    "variables": {
+                        "glossary": {"type": "list", "access": "push"}
+                    },
+                    "description": "Declares an acronym, where the content is what it stands for. For instance, [acronym-def WASI](WebAssembly System Interface). Use it with [acr]."
+                },
+                {
+                    "from": "gloss-def",
+                    "to": ["any"],
+                    "arguments": [
+                        {"name": "key", "description": "The key used to refer to the term"},
+                        {"name": "name", "default": "", "description": "The term as it is written in the text, defaults to the key"},
+                    ],
+                    "variables": {
+                        "glossary": {"type": "list", "access": "push"}
+                    },
+                    "unknown-content": true,
+                    "description": "Declares a term for the glossary, where the content is its description. Use it with [gls]."
+                },
+                {
+                    "from": "acr",
+                    "to": ["html", "latex"],
+                    "arguments": [
+                        {"name": "form", "default": "auto", "type": ["auto", "short", "long", "full"], "description": "How to write the acronym. By default it is written in full on its first use and abbreviated after that."},
+                    ],
+                    "variables": {
+                        "glossary-uses": {"type": "list", "access": "push"}
+                    },
+                    "description": "Uses an acronym declared using [acronym-def], for instance [acr](WASI). It is linked to the list of abbreviations."
+                },
+                {
+                    "from": "gls",
+                    "to": ["html", "latex"],
+                    "arguments": [
+                        {"name": "form", "default": "auto", "type": ["auto", "short", "long", "full"], "description": "Only used for acronyms"},
+                    ],
+                    "variables": {
+                        "glossary-uses": {"type": "list", "access": "push"}
+                    },
+                    "description": "Uses a term declared using [gloss-def], for instance [gls](AST). It is linked to the glossary."
+                },
+                {
+                    "from": "glossary-use",
+                    "to": ["html", "latex"],
+                    "arguments": [
+                        {"name": "id", "description": "The id of the use", "type": "u64"},
+                        {"name": "form", "default": "auto", "type": ["auto", "short", "long", "full"], "description": "How to write the acronym"},
+                    ],
+                    "variables": {
+                        "glossary": {"type": "list", "access": "read"},
+                        "glossary-uses": {"type": "list", "access": "read"}
+                    },
+                    "description": "Do not use this module. It is generated when using [acr] and [gls]."
+                },
+                {
+                    "from": "glossary",
+                    "to": ["html", "latex"],
+                    "arguments": [
+                        {"name": "type", "default": "terms", "type": ["terms", "acronyms"], "description": "Whether to list the terms or the acronyms"},
+                    ],
+                    "variables": {
+                        "glossary": {"type": "list", "access": "read"}
+                    },
+                    "description": "Lists all terms declared using [gloss-def], or all acronyms declared using [acronym-def], in alphabetical order. The acronyms are also listed on the abbreviations page of the front matter, so only use one of them."
+                },
+                {
+                    "from": "cite",
+                    "to": ["any"],
+                    "arguments": [
+                        {"name": "note", "default": "", "description": "A note such as a page number, for instance [cite \"p. 43\"](knuth)"},
+                    ],
+                    "variables": {
+                        "citations": {"type": "list", "access": "push"}
+                    },
+                    "description": "Cites one or more comma separated sources from the BibTeX file given by the constant 'sources' (sources.bib by default)."
+                },
+                {
+                    "from": "citation",
+                    "to": ["any"],
+                    "arguments": [
+                        {"name": "note", "default": "", "description": "A note such as a page number"},
+                    ],
+                    "variables": {
+                        "citations": {"type": "list", "access": "read"},
+                        "sources": {"type": "const", "access": "read"},
+                        "citation_style": {"type": "const", "access": "read"}
+                    },
+                    "description": "Do not use this module. It is generated when using [cite]."
+                },
+                {
+                    "from": "bibliography",
+                    "to": ["any"],
+                    "arguments": [
+                        {"name": "file", "default": "", "description": "Deprecated, set the constant 'sources' instead"},
+                    ],
+                    "variables": {
+                        "citations": {"type": "list", "access": "read"},
+                        "sources": {"type": "const", "access": "read"},
+                        "citation_style": {"type": "const", "access": "read"}
+                    },
+                    "description": "Lists every cited source, formatted according to the constant 'citation_style': 'ieee' (default), 'apa', 'harvard' or 'vancouver'. Sources that are never cited are reported and left out."
+                },
+                {
+                    "from": "index",
+                    "to": ["html", "latex"],
+                    "arguments": [
+                        {"name": "see", "default": "", "description": "Another term to see instead. The occurrence is then not listed."},
+                        {"name": "see-also", "default": "", "description": "Another term to also see"},
+                    ],
+                    "variables": {
+                        "index": {"type": "list", "access": "push"},
+                        "imports": {"type": "set", "access": "add"}
+                    },
+                    "description": "Marks an occurrence of a term for the index, for instance [index](parser) or [index](parser!recursive descent) for a subterm. Nothing is shown in the text."
+                },
+                {
+                    "from": "print-index",
+                    "to": ["html", "latex"],
+                    "arguments": [],
+                    "variables": {
+                        "index": {"type": "list", "access": "read"},
+                        "imports": {"type": "set", "access": "add"}
+                    },
+                    "description": "Prints the index of all terms marked using [index], in alphabetical order. In HTML every occurrence is linked to."
+                },
+                {
+                    "from": "contribution",
+                    "to": ["any"],
+                    "arguments": [
+                        {"name": "author", "description": "The name or e-mail address of the author, as written in the list of authors"},
+                        {"name": "sections", "default": "", "description": "Comma separated chapters or sections the author has written"},
+                        {"name": "implementation", "default": "", "description": "Comma separated parts of the implementation the author has done"},
+                    ],
+                    "variables": {
+                        "contributions": {"type": "list", "access": "push"}
+                    },
+                    "unknown-content": true,
+                    "description": "Records the contributions of an author for the contribution report. The content is an optional statement of the contributions. The report is placed according to the constant 'contribution_report', which is 'front-matter' (default), 'appendix' or 'none'."
+                },
+                {
+                    "from": "written-by",
+                    "to": ["any"],
+                    "arguments": [
+                        {"name": "section", "description": "The chapter or section that was written"},
+                    ],
+                    "variables": {
+                        "contributions": {"type": "list", "access": "push"}
+                    },
+                    "description": "Notes the comma separated authors of a chapter or section, for instance [written-by section=Method] Alice, Bob. The notes make up a matrix of sections and authors in the contribution report, see [contribution]."
+                },
+                {
+                    "from": "float-kind",
+                    "to": ["any"],
+                    "arguments": [
+                        {"name": "module", "description": "The module that floats of this kind wraps"},
+                        {"name": "display-name", "default": "", "description": "The name shown in front of the number, defaults to the capitalized name of the kind"},
+                        {"name": "numbered-as", "default": "", "description": "Share numbering and LaTeX environment with another kind, for instance 'figure'"},
+                        {"name": "native-caption", "default": "false", "type": ["true", "false"], "description": "Whether the wrapped module takes caption and label arguments itself, like [image] and [table]"},
+                    ],
+                    "variables": {
+                        "float-kinds": {"type": "list", "access": "push"}
+                    },
+                    "description": "Declares a new kind of float, named by the content of the module. For instance, [float-kind module=code] algorithm."
+                },
+                {
+                    "from": "list-of",
+                    "to": ["html", "latex"],
+                    "arguments": [],
+                    "variables": {
+                        "floats": {"type": "list", "access": "read"},
+                        "float-kinds": {"type": "list", "access": "read"},
+                        "structure": {"type": "list", "access": "read"}
+                    },
+                    "description": "Lists all floats of the given kind, such as [list-of](figure)."
+                },
                 {
                     "from": "label",
                     "to": ["html", "latex"],
@@ -768,7 +1278,8 @@ fn manifest() -> String {
                     "to": ["any"],
                     "arguments": [],
                     "variables": {
-                        "structure": {"type": "list", "access": "read"}
+                        "structure": {"type": "list", "access": "read"},
+                        "float-kinds": {"type": "list", "access": "read"},
                     }
                 },
             ]
@@ -0,0 +1,185 @@
+use crate::{
+    escape::escape_html,
+    how_to_cite::{ThesisCitation, THESIS_TYPE},
+    DocSettings,
+};
+use serde_json::{json, Map, Value};
+
+/// The metadata in the head of the HTML document: Highwire Press tags for
+/// Google Scholar, Dublin Core, a schema.org JSON-LD block and OpenGraph tags
+/// for link previews
+pub(crate) fn html_metadata(settings: &DocSettings) -> String {
+    let citation = ThesisCitation::from_settings(settings);
+    let institution = citation.institution();
+    let keywords: Vec<&str> = settings
+        .keywords
+        .as_deref()
+        .unwrap_or_default()
+        .split([',', ';'])
+        .map(str::trim)
+        .filter(|keyword| !keyword.is_empty())
+        .collect();
+    // names are written "Family, Given" like in the BibTeX entry
+    let authors: Vec<String> = citation
+        .authors
+        .iter()
+        .map(|(family, given)| {
+            if given.is_empty() {
+                family.clone()
+            } else {
+                format!("{family}, {given}")
+            }
+        })
+        .collect();
+
+    let mut tags = vec![
+        meta("name", "citation_title", &citation.title),
+        meta("name", "citation_publication_date", &citation.year),
+        meta("name", "citation_dissertation_institution", &institution),
+    ];
+    for author in &authors {
+        tags.push(meta("name", "citation_author", author));
+    }
+    if !keywords.is_empty() {
+        tags.push(meta("name", "citation_keywords", &keywords.join("; ")));
+    }
+
+    tags.push(r#"<link rel="schema.DC" href="http://purl.org/dc/elements/1.1/">"#.to_string());
+    tags.push(meta("name", "DC.title", &citation.title));
+    for author in &authors {
+        tags.push(meta("name", "DC.creator", author));
+    }
+    tags.push(meta("name", "DC.date", &citation.year));
+    tags.push(meta("name", "DC.publisher", &institution));
+    tags.push(meta("name", "DC.type", "Text"));
+    for keyword in &keywords {
+        tags.push(meta("name", "DC.subject", keyword));
+    }
+
+    tags.push(meta("property", "og:title", &citation.title));
+    tags.push(meta("property", "og:type", "article"));
+    for author in &settings.authors {
+        tags.push(meta("property", "article:author", &author.name));
+    }
+    if let Some(cover) = &settings.cover_art {
+        tags.push(meta("property", "og:image", cover));
+        if let Some(description) = &settings.cover_art_description {
+            tags.push(meta("property", "og:image:alt", description));
+        }
+    }
+
+    let mut thesis = Map::new();
+    thesis.insert("@context".into(), json!("https://schema.org"));
+    thesis.insert("@type".into(), json!("Thesis"));
+    thesis.insert("name".into(), json!(settings.get_title()));
+    if let Some(subtitle) = &settings.subtitle {
+        thesis.insert("alternativeHeadline".into(), json!(subtitle));
+    }
+    let people: Vec<Value> = settings
+        .authors
+        .iter()
+        .map(|author| json!({"@type": "Person", "name": author.name}))
+        .collect();
+    thesis.insert("author".into(), json!(people));
+    thesis.insert("datePublished".into(), json!(citation.year));
+    thesis.insert("inSupportOf".into(), json!(THESIS_TYPE));
+    thesis.insert(
+        "sourceOrganization".into(),
+        json!({"@type": "Organization", "name": institution}),
+    );
+    if !keywords.is_empty() {
+        thesis.insert("keywords".into(), json!(keywords));
+    }
+    if let Some(cover) = &settings.cover_art {
+        thesis.insert("image".into(), json!(cover));
+    }
+    // "</" would end the script element early
+    let json_ld = serde_json::to_string_pretty(&Value::Object(thesis))
+        .unwrap()
+        .replace("</", "<\\/");
+    tags.push(format!(
+        "<script type=\"application/ld+json\">\n{json_ld}\n</script>"
+    ));
+
+    tags.join("\n") + "\n"
+}
+
+/// The metadata in the Info dictionary of the PDF, which hyperref also
+/// writes to the XMP metadata of a PDF/A document. `language` is the tag of
+/// the main language of the document.
+///
+/// The values are LaTeX, like on the title pages, and hyperref turns them
+/// into plain text, so that a title such as `The \LaTeX{} Companion` reads
+/// "The LaTeX Companion" in the PDF viewer.
+pub(crate) fn pdf_metadata(settings: &DocSettings, language: &str) -> String {
+    let title = match &settings.subtitle {
+        Some(subtitle) => format!("{}: {subtitle}", settings.get_title()),
+        None => settings.get_title(),
+    };
+    let subject = match &settings.subject {
+        Some(subject) => format!("{THESIS_TYPE} in {subject}"),
+        None => THESIS_TYPE.to_string(),
+    };
+
+    let mut options = vec![
+        format!("pdftitle={{{title}}}"),
+        format!("pdfauthor={{{}}}", settings.author_names()),
+        format!("pdfsubject={{{subject}}}"),
+    ];
+    if let Some(keywords) = &settings.keywords {
+        options.push(format!("pdfkeywords={{{keywords}}}"));
+    }
+    options.push(format!("pdflang={{{language}}}"));
+
+    format!("\\hypersetup{{\n    {}\n}}\n", options.join(",\n    "))
+}
+
+/// The PDF/A levels that `pdf_a` may be set to. The "a" levels are left out
+/// since they need a tagged PDF, which the template doesn't produce.
+const PDF_A_LEVELS: [&str; 5] = ["1b", "2b", "2u", "3b", "3u"];
+
+/// `\DocumentMetadata`, which has to come before `\documentclass`, when the
+/// constant `pdf_a` asks for a PDF/A document. The PDF management of LaTeX
+/// then writes the XMP metadata and embeds the sRGB colour profile as the
+/// output intent.
+pub(crate) fn document_metadata(settings: &DocSettings, language: &str) -> Option<String> {
+    let level = settings
+        .pdf_a
+        .as_deref()
+        .map(str::trim)
+        .filter(|level| !level.is_empty())?
+        .to_lowercase();
+    let level = level.strip_prefix("a-").unwrap_or(&level);
+    let level = match level {
+        level if PDF_A_LEVELS.contains(&level) => level,
+        "1a" | "2a" | "3a" => {
+            let fallback = match level {
+                "1a" => "1b",
+                "2a" => "2b",
+                _ => "3b",
+            };
+            eprintln!(
+                "The PDF/A level '{level}' needs a tagged PDF, which the template doesn't produce, using {fallback}."
+            );
+            fallback
+        }
+        _ => {
+            eprintln!(
+                "Unknown PDF/A level '{level}', using 2b. The levels are {}.",
+                PDF_A_LEVELS.join(", ")
+            );
+            "2b"
+        }
+    };
+
+    Some(format!(
+        "\\DocumentMetadata{{pdfstandard=A-{level}, lang={language}}}\n"
+    ))
+}
+
+fn meta(attribute: &str, name: &str, content: &str) -> String {
+    format!(
+        r#"<meta {attribute}="{name}" content="{}">"#,
+        escape_html(content.trim())
+    )
+}
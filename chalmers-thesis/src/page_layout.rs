@@ -0,0 +1,62 @@
+use std::env;
+
+/// The layout of the PDF, chosen using the constant `layout`. The print
+/// layout is two-sided with chapters starting on the right, and has blank
+/// back pages so that the front matter is printed on its own sheets. The
+/// screen layout is one-sided, without blank pages and with coloured links.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) enum PageLayout {
+    Print,
+    Screen,
+}
+
+impl PageLayout {
+    pub(crate) fn from_env() -> Self {
+        let Ok(layout) = env::var("layout") else {
+            return PageLayout::Print;
+        };
+
+        match layout.trim().to_lowercase().as_str() {
+            "print" => PageLayout::Print,
+            "screen" => PageLayout::Screen,
+            _ => {
+                eprintln!("Unknown layout '{layout}'. Use either print or screen.");
+                PageLayout::Print
+            }
+        }
+    }
+
+    /// The options of the document class that depend on the layout
+    pub(crate) fn class_options(&self) -> &'static str {
+        match self {
+            PageLayout::Print => "twoside,openright",
+            PageLayout::Screen => "oneside,openany",
+        }
+    }
+
+    /// `\layout`, which `settings.tex` uses to choose the headers and footers
+    pub(crate) fn definition(&self) -> &'static str {
+        match self {
+            PageLayout::Print => "\\def\\layout{2}\n",
+            PageLayout::Screen => "\\def\\layout{1}\n",
+        }
+    }
+
+    /// Hyperref, with links that aren't marked in print and coloured links and
+    /// open bookmarks on screen
+    pub(crate) fn hyperref_import(&self) -> String {
+        match self {
+            PageLayout::Print => "\\usepackage[hidelinks]{hyperref}".to_string(),
+            PageLayout::Screen => "\\usepackage[colorlinks,linkcolor={blue!50!black},citecolor={green!40!black},urlcolor={blue!70!black},bookmarksopen,bookmarksnumbered]{hyperref}".to_string(),
+        }
+    }
+
+    /// An empty page on the back of the previous one, which is left out on
+    /// screen
+    pub(crate) fn blank_back_page(&self) -> &'static str {
+        match self {
+            PageLayout::Print => "\n\\newpage\n\\thispagestyle{empty}\n\\mbox{}",
+            PageLayout::Screen => "",
+        }
+    }
+}
@@ -0,0 +1,469 @@
+/// Packages that have to be loaded before another one, as (before, after)
+const LOAD_ORDER: [(&str, &str); 10] = [
+    ("fontenc", "babel"),
+    ("inputenc", "babel"),
+    ("fontspec", "babel"),
+    ("fontspec", "polyglossia"),
+    ("babel", "csquotes"),
+    ("babel", "biblatex"),
+    ("polyglossia", "csquotes"),
+    ("polyglossia", "biblatex"),
+    ("csquotes", "biblatex"),
+    ("caption", "subcaption"),
+];
+
+/// Packages that are loaded after hyperref. Every other package is loaded
+/// before it, since hyperref redefines commands from many of them.
+const AFTER_HYPERREF: [&str; 5] = [
+    "bookmark",
+    "cleveref",
+    "glossaries",
+    "glossaries-extra",
+    "hypcap",
+];
+
+/// Packages that cannot be used together
+const CONFLICTS: [(&str, &str); 8] = [
+    ("babel", "polyglossia"),
+    ("fontspec", "inputenc"),
+    ("fontspec", "fontenc"),
+    ("biblatex", "natbib"),
+    ("biblatex", "cite"),
+    ("subcaption", "subfig"),
+    ("subcaption", "subfigure"),
+    ("subfig", "subfigure"),
+];
+
+/// A package in the preamble, with the commands that belong to it
+struct Package {
+    name: String,
+    options: Vec<String>,
+    /// Commands written before `\usepackage`, such as `\PassOptionsToPackage`
+    before: Vec<String>,
+    /// Commands that use the package, such as `\makeindex` after `imakeidx`
+    setup: Vec<String>,
+}
+
+impl Package {
+    fn to_latex(&self, latex: &mut Vec<String>) {
+        latex.extend(self.before.iter().cloned());
+        if self.options.is_empty() {
+            latex.push(format!("\\usepackage{{{}}}", self.name));
+        } else {
+            latex.push(format!(
+                "\\usepackage[{}]{{{}}}",
+                self.options.join(","),
+                self.name
+            ));
+        }
+        latex.extend(self.setup.iter().cloned());
+    }
+}
+
+/// The packages of a LaTeX document and the commands that set them up. The
+/// imports come from the template and from other modules, and may load the
+/// same package more than once, so the packages are merged and then ordered
+/// by what has to be loaded before what.
+#[derive(Default)]
+pub(crate) struct Preamble {
+    packages: Vec<Package>,
+    /// Commands that aren't part of a package import
+    commands: Vec<String>,
+}
+
+impl Preamble {
+    /// Add an import, which is one or more lines of LaTeX. Lines after a
+    /// `\usepackage` belong to that package and are kept right after it.
+    pub(crate) fn add(&mut self, import: &str) {
+        let mut pending: Vec<String> = Vec::new();
+        let mut current: Option<Package> = None;
+
+        for line in import
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+        {
+            match parse_usepackage(line) {
+                Some((names, options, rest)) => {
+                    if let Some(package) = current.take() {
+                        self.add_package(package);
+                    }
+                    let mut packages: Vec<Package> = names
+                        .into_iter()
+                        .map(|name| Package {
+                            name,
+                            options: options.clone(),
+                            before: Vec::new(),
+                            setup: Vec::new(),
+                        })
+                        .collect();
+                    // `\usepackage{a,b}` loads several packages, the
+                    // commands go with the last of them
+                    let Some(mut last) = packages.pop() else {
+                        continue;
+                    };
+                    for package in packages {
+                        self.add_package(package);
+                    }
+                    last.before.append(&mut pending);
+                    last.setup.extend(rest);
+                    current = Some(last);
+                }
+                None => match &mut current {
+                    Some(package) => package.setup.push(line.to_string()),
+                    None => pending.push(line.to_string()),
+                },
+            }
+        }
+
+        match current {
+            Some(package) => self.add_package(package),
+            None => {
+                for command in pending {
+                    if !self.commands.contains(&command) {
+                        self.commands.push(command);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Add a package, or merge it with the same package imported before
+    fn add_package(&mut self, package: Package) {
+        let Some(existing) = self
+            .packages
+            .iter_mut()
+            .find(|existing| existing.name == package.name)
+        else {
+            self.packages.push(package);
+            return;
+        };
+
+        for option in package.options {
+            if existing.options.contains(&option) {
+                continue;
+            }
+
+            let key = option_key(&option);
+            if let Some(other) = existing
+                .options
+                .iter()
+                .find(|other| option_key(other) == key)
+            {
+                eprintln!(
+                    "The package '{}' is imported with both '{other}' and '{option}', '{other}' is used.",
+                    package.name
+                );
+            } else if matches!(package.name.as_str(), "babel" | "fontenc")
+                && !existing.options.is_empty()
+            {
+                // the last language is the main language of the document, and
+                // the last encoding is the default one
+                let main = existing.options.len() - 1;
+                existing.options.insert(main, option);
+            } else {
+                existing.options.push(option);
+            }
+        }
+
+        for command in package.before {
+            if !existing.before.contains(&command) {
+                existing.before.push(command);
+            }
+        }
+        for command in package.setup {
+            if !existing.setup.contains(&command) {
+                existing.setup.push(command);
+            }
+        }
+    }
+
+    /// The packages in the order they are loaded in. Packages keep the order
+    /// they were added in, unless one has to be loaded before another.
+    fn ordered(&self) -> Vec<&Package> {
+        let mut remaining: Vec<&Package> = self.packages.iter().collect();
+        let mut ordered = Vec::new();
+
+        while !remaining.is_empty() {
+            let next = remaining
+                .iter()
+                .position(|package| {
+                    !remaining
+                        .iter()
+                        .any(|other| loads_before(&other.name, &package.name))
+                })
+                .unwrap_or_else(|| {
+                    eprintln!("The packages cannot be ordered, since they depend on each other.");
+                    0
+                });
+            ordered.push(remaining.remove(next));
+        }
+
+        ordered
+    }
+
+    /// The imported packages that cannot be used together
+    fn conflicts(&self) -> Vec<(&'static str, &'static str)> {
+        let imported = |name: &str| self.packages.iter().any(|package| package.name == name);
+        CONFLICTS
+            .into_iter()
+            .filter(|(first, second)| imported(first) && imported(second))
+            .collect()
+    }
+
+    /// Write the preamble, and warn about packages that cannot be used together
+    pub(crate) fn to_latex(&self) -> String {
+        for (first, second) in self.conflicts() {
+            eprintln!("The packages '{first}' and '{second}' cannot be used together.");
+        }
+
+        let mut latex = Vec::new();
+        for package in self.ordered() {
+            package.to_latex(&mut latex);
+        }
+        latex.extend(self.commands.iter().cloned());
+
+        latex.join("\n") + "\n"
+    }
+}
+
+/// Whether the package `first` has to be loaded before `second`
+fn loads_before(first: &str, second: &str) -> bool {
+    if first == second {
+        false
+    } else if second == "hyperref" {
+        !AFTER_HYPERREF.contains(&first)
+    } else if first == "hyperref" {
+        AFTER_HYPERREF.contains(&second)
+    } else {
+        LOAD_ORDER.contains(&(first, second))
+    }
+}
+
+/// The part of a package option before '=', such as "style" in "style=apa"
+fn option_key(option: &str) -> &str {
+    option.split_once('=').map_or(option, |(key, _)| key).trim()
+}
+
+/// Parse `\usepackage[options]{names}` into the package names, the options
+/// and anything that follows on the same line
+fn parse_usepackage(line: &str) -> Option<(Vec<String>, Vec<String>, Option<String>)> {
+    let rest = line
+        .strip_prefix("\\usepackage")
+        .or_else(|| line.strip_prefix("\\RequirePackage"))?
+        .trim_start();
+
+    let (options, rest) = match rest.strip_prefix('[') {
+        Some(rest) => {
+            let (options, rest) = split_group(rest, '[', ']')?;
+            (split_options(options), rest.trim_start())
+        }
+        None => (Vec::new(), rest),
+    };
+    let (names, rest) = split_group(rest.strip_prefix('{')?, '{', '}')?;
+    let names = names
+        .split(',')
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .map(str::to_string)
+        .collect();
+
+    // a date such as [2020/01/01] or a comment is left out
+    let rest = rest.trim();
+    let rest = if rest.is_empty() || rest.starts_with('%') || rest.starts_with('[') {
+        None
+    } else {
+        Some(rest.to_string())
+    };
+
+    Some((names, options, rest))
+}
+
+/// Split text after an opening bracket at the matching closing bracket
+fn split_group(text: &str, open: char, close: char) -> Option<(&str, &str)> {
+    let mut depth = 0;
+    for (index, c) in text.char_indices() {
+        match c {
+            c if c == close && depth == 0 => return Some((&text[..index], &text[index + 1..])),
+            '{' => depth += 1,
+            '}' => depth -= 1,
+            c if c == open => depth += 1,
+            c if c == close => depth -= 1,
+            _ => {}
+        }
+    }
+
+    None
+}
+
+/// Split package options at commas that aren't within braces
+fn split_options(options: &str) -> Vec<String> {
+    let mut result = Vec::new();
+    let mut depth = 0;
+    let mut current = String::new();
+    for c in options.chars() {
+        match c {
+            '{' => depth += 1,
+            '}' => depth -= 1,
+            ',' if depth == 0 => {
+                result.push(current.trim().to_string());
+                current.clear();
+                continue;
+            }
+            _ => {}
+        }
+        current.push(c);
+    }
+    result.push(current.trim().to_string());
+
+    result.retain(|option| !option.is_empty());
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn preamble(imports: &[&str]) -> Preamble {
+        let mut preamble = Preamble::default();
+        for import in imports {
+            preamble.add(import);
+        }
+        preamble
+    }
+
+    fn names(preamble: &Preamble) -> Vec<&str> {
+        preamble
+            .ordered()
+            .iter()
+            .map(|package| package.name.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn parses_usepackage() {
+        assert_eq!(
+            parse_usepackage(r"\usepackage{amsmath}"),
+            Some((vec!["amsmath".to_string()], vec![], None))
+        );
+        assert_eq!(
+            parse_usepackage(r"\usepackage[style=apa, backend={biber,bibtex}]{biblatex}"),
+            Some((
+                vec!["biblatex".to_string()],
+                vec![
+                    "style=apa".to_string(),
+                    "backend={biber,bibtex}".to_string()
+                ],
+                None
+            ))
+        );
+        assert_eq!(
+            parse_usepackage(r"\RequirePackage{ amssymb , bm }[2020/01/01] % maths"),
+            Some((vec!["amssymb".to_string(), "bm".to_string()], vec![], None))
+        );
+        assert_eq!(
+            parse_usepackage(r"\usepackage{imakeidx}\makeindex"),
+            Some((
+                vec!["imakeidx".to_string()],
+                vec![],
+                Some(r"\makeindex".to_string())
+            ))
+        );
+        assert_eq!(parse_usepackage(r"\usepackage[draft{amsmath}"), None);
+        assert_eq!(parse_usepackage(r"\newcommand{\R}{\mathbb{R}}"), None);
+    }
+
+    #[test]
+    fn merges_options_of_the_same_package() {
+        let preamble = preamble(&[
+            r"\usepackage[hidelinks]{hyperref}",
+            r"\usepackage[hidelinks,unicode]{hyperref}",
+            r"\usepackage[style=apa]{biblatex}",
+            r"\usepackage[style=ieee,backend=biber]{biblatex}",
+        ]);
+
+        assert_eq!(preamble.packages.len(), 2);
+        let options = |name: &str| {
+            preamble
+                .packages
+                .iter()
+                .find(|package| package.name == name)
+                .unwrap()
+                .options
+                .clone()
+        };
+        assert_eq!(options("hyperref"), ["hidelinks", "unicode"]);
+        // the option imported first is kept
+        assert_eq!(options("biblatex"), ["style=apa", "backend=biber"]);
+    }
+
+    #[test]
+    fn keeps_the_main_language_last() {
+        let preamble = preamble(&[
+            r"\usepackage[english]{babel}",
+            r"\usepackage[swedish]{babel}",
+        ]);
+        assert_eq!(preamble.packages[0].options, ["swedish", "english"]);
+    }
+
+    #[test]
+    fn keeps_commands_with_their_package() {
+        let preamble = preamble(&[
+            "\\PassOptionsToPackage{hyphens}{url}\n\\usepackage{url}\n\\urlstyle{same}",
+            "\\urlstyle{same}",
+            "\\setlength{\\parskip}{1em}",
+        ]);
+
+        assert_eq!(
+            preamble.to_latex(),
+            "\\PassOptionsToPackage{hyphens}{url}\n\\usepackage{url}\n\\urlstyle{same}\n\\urlstyle{same}\n\\setlength{\\parskip}{1em}\n"
+        );
+    }
+
+    #[test]
+    fn orders_packages_by_what_they_depend_on() {
+        let preamble = preamble(&[
+            r"\usepackage{biblatex}",
+            r"\usepackage{cleveref}",
+            r"\usepackage{hyperref}",
+            r"\usepackage{csquotes}",
+            r"\usepackage{babel}",
+            r"\usepackage{amsmath}",
+            r"\usepackage[T1]{fontenc}",
+        ]);
+
+        assert_eq!(
+            names(&preamble),
+            ["amsmath", "fontenc", "babel", "csquotes", "biblatex", "hyperref", "cleveref"]
+        );
+    }
+
+    #[test]
+    fn loads_packages_after_hyperref() {
+        assert!(loads_before("amsmath", "hyperref"));
+        assert!(!loads_before("hyperref", "amsmath"));
+        assert!(loads_before("hyperref", "cleveref"));
+        assert!(!loads_before("cleveref", "hyperref"));
+        assert!(!loads_before("hyperref", "hyperref"));
+
+        let preamble = preamble(&[r"\usepackage{bookmark}", r"\usepackage{hyperref}"]);
+        assert_eq!(names(&preamble), ["hyperref", "bookmark"]);
+    }
+
+    #[test]
+    fn finds_conflicting_packages() {
+        let preamble = preamble(&[
+            r"\usepackage{babel}",
+            r"\usepackage{subcaption}",
+            r"\usepackage{natbib}",
+            r"\usepackage{polyglossia}",
+            r"\usepackage{subfig}",
+        ]);
+        assert_eq!(
+            preamble.conflicts(),
+            [("babel", "polyglossia"), ("subcaption", "subfig")]
+        );
+        // both packages are still loaded, it is only a warning
+        assert_eq!(preamble.to_latex().matches("\\usepackage").count(), 5);
+    }
+}
@@ -0,0 +1,147 @@
+use serde_json::Value;
+use std::env;
+
+/// The role someone has in supervising or examining the thesis
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) enum Role {
+    Supervisor,
+    CoSupervisor,
+    IndustrialSupervisor,
+    Examiner,
+    /// The teacher grading the thesis (rättande lärare)
+    Grader,
+    /// Any other role, shown as it is written
+    Other(String),
+}
+
+impl Role {
+    fn parse(role: &str) -> Self {
+        match role.trim().to_lowercase().as_str() {
+            "supervisor" => Role::Supervisor,
+            "co-supervisor" => Role::CoSupervisor,
+            "industrial-supervisor" => Role::IndustrialSupervisor,
+            "examiner" => Role::Examiner,
+            "grader" => Role::Grader,
+            _ => Role::Other(role.trim().to_string()),
+        }
+    }
+
+    /// The label shown in front of the name, in English and Swedish
+    pub(crate) fn label(&self) -> &str {
+        match self {
+            Role::Supervisor => "Supervisor (handledare)",
+            Role::CoSupervisor => "Co-supervisor (biträdande handledare)",
+            Role::IndustrialSupervisor => "Industrial supervisor (företagshandledare)",
+            Role::Examiner => "Examiner (examinator)",
+            Role::Grader => "Graded by teacher (rättande lärare)",
+            Role::Other(label) => label,
+        }
+    }
+}
+
+/// A supervisor or examiner. Entries in the `supervisors` and `examiners`
+/// lists are written as `Name, Organisation`, optionally preceded by a role
+/// such as `co-supervisor: Name, Organisation`, or as a JSON object with the
+/// keys `name`, `organisation` and `role`.
+#[derive(Clone)]
+pub(crate) struct Supervisor {
+    pub(crate) name: String,
+    pub(crate) organisation: Option<String>,
+    pub(crate) role: Role,
+}
+
+impl Supervisor {
+    fn parse(entry: &str, default_role: Role) -> Self {
+        let entry = entry.trim();
+
+        if entry.starts_with('{') {
+            match serde_json::from_str::<Value>(entry) {
+                Ok(json) => {
+                    let get = |key: &str| {
+                        json[key]
+                            .as_str()
+                            .map(str::trim)
+                            .filter(|value| !value.is_empty())
+                            .map(str::to_string)
+                    };
+                    return Self {
+                        name: get("name").unwrap_or_default(),
+                        organisation: get("organisation").or_else(|| get("department")),
+                        role: get("role").map_or(default_role, |role| Role::parse(&role)),
+                    };
+                }
+                Err(error) => eprintln!("Invalid supervisor '{entry}': {error}"),
+            }
+        }
+
+        // only known roles are taken from in front of a colon, so that names
+        // containing one are kept as they are
+        let (role, rest) = match entry.split_once(':') {
+            Some((role, rest)) if !matches!(Role::parse(role), Role::Other(_)) => {
+                (Role::parse(role), rest)
+            }
+            _ => (default_role, entry),
+        };
+
+        let (name, organisation) = match rest.split_once(',') {
+            Some((name, organisation)) => (name, Some(organisation.trim().to_string())),
+            None => (rest, None),
+        };
+
+        Self {
+            name: name.trim().to_string(),
+            organisation: organisation.filter(|organisation| !organisation.is_empty()),
+            role,
+        }
+    }
+
+    /// Read everyone supervising and examining the thesis, ordered by role.
+    /// The constants `supervisor`, `course_examiner` and `examiner` (each
+    /// with a `_department`) are still read, after the lists.
+    pub(crate) fn read_all() -> Vec<Self> {
+        let mut people: Vec<Self> = read_list("supervisors")
+            .iter()
+            .map(|entry| Self::parse(entry, Role::Supervisor))
+            .chain(
+                read_list("examiners")
+                    .iter()
+                    .map(|entry| Self::parse(entry, Role::Examiner)),
+            )
+            .collect();
+
+        for (name, role) in [
+            ("supervisor", Role::Supervisor),
+            ("course_examiner", Role::Examiner),
+            ("examiner", Role::Grader),
+        ] {
+            if let Ok(person) = env::var(name) {
+                people.push(Self {
+                    name: person,
+                    organisation: env::var(format!("{name}_department")).ok(),
+                    role,
+                });
+            }
+        }
+
+        if people.is_empty() {
+            eprintln!("The lists 'supervisors' and 'examiners' were empty.");
+        }
+
+        // a stable sort, so people with the same role keep their order
+        people.sort_by(|a, b| a.role.cmp(&b.role));
+        people
+    }
+
+    /// The name of the person followed by their organisation
+    pub(crate) fn name_and_organisation(&self) -> String {
+        match &self.organisation {
+            Some(organisation) => format!("{}, {organisation}", self.name),
+            None => self.name.clone(),
+        }
+    }
+}
+
+fn read_list(name: &str) -> Vec<String> {
+    let var = env::var(name).unwrap_or_else(|_| "[]".to_string());
+    serde_json::from_str(&var).unwrap()
+}
@@ -0,0 +1,1368 @@
+use crate::{escape::escape_latex, Error};
+use std::{collections::HashMap, f64::consts::PI, fmt::Write, fs};
+
+/// Include an SVG file with a given width, such as `\\linewidth`, by drawing
+/// it using PGF. Images using features that can't be converted are an error,
+/// which names the feature.
+pub(crate) fn include_svg(path: &str, width: &str) -> Result<String, Error> {
+    let picture = fs::read_to_string(path)
+        .map_err(|error| error.to_string())
+        .and_then(|svg| svg_to_pgf(&svg))
+        .map_err(|error| Error::Svg(format!("'{path}' could not be drawn using PGF: {error}")))?;
+
+    Ok(format!("\\resizebox{{{width}}}{{!}}{{{picture}}}"))
+}
+
+/// Convert an SVG image into a `pgfpicture` of the same size. Paths, basic
+/// shapes, groups, transforms, clip paths and plain text are supported, but
+/// not gradients, patterns, masks, filters or embedded images.
+pub(crate) fn svg_to_pgf(svg: &str) -> Result<String, String> {
+    let root = parse_xml(svg)?;
+    if root.name != "svg" {
+        return Err(format!("the root element is <{}> and not <svg>", root.name));
+    }
+
+    // the size of the picture in px, and the area of the image it shows
+    let view_box = match root.attributes.get("viewBox") {
+        Some(view_box) => {
+            let numbers = parse_numbers(view_box)?;
+            let [x, y, width, height] = numbers[..] else {
+                return Err(format!("invalid viewBox '{view_box}'"));
+            };
+            Some((x, y, width, height))
+        }
+        None => None,
+    };
+    let size = |name: &str, index: usize| match (root.attributes.get(name), view_box) {
+        (Some(value), Some(view_box)) if value.ends_with('%') => {
+            let view_box = [view_box.2, view_box.3];
+            length(value, view_box[index])
+        }
+        (Some(value), _) => length(value, 0.0),
+        (None, Some(view_box)) => Ok([view_box.2, view_box.3][index]),
+        (None, None) => Err(format!("the image has neither a {name} nor a viewBox")),
+    };
+    let (width, height) = (size("width", 0)?, size("height", 1)?);
+    let (x, y, view_width, view_height) = view_box.unwrap_or((0.0, 0.0, width, height));
+    if view_width <= 0.0 || view_height <= 0.0 {
+        return Err("the image is empty".to_string());
+    }
+
+    // PGF uses points with the y axis pointing up, while SVG uses pixels
+    // with the y axis pointing down
+    let (width, height) = (width * PT_PER_PX, height * PT_PER_PX);
+    let (scale_x, scale_y) = (width / view_width, height / view_height);
+    let matrix = Matrix([
+        scale_x,
+        0.0,
+        0.0,
+        -scale_y,
+        -x * scale_x,
+        height + y * scale_y,
+    ]);
+
+    let mut clip_paths = HashMap::new();
+    collect_clip_paths(&root, &mut clip_paths);
+    let mut renderer = Renderer {
+        clip_paths,
+        viewport: (view_width, view_height),
+        output: String::new(),
+        state: HashMap::new(),
+        saved: Vec::new(),
+    };
+
+    writeln!(
+        renderer.output,
+        "\\begin{{pgfpicture}}\n\\pgfpathrectangle{{\\pgfpointorigin}}{{\\pgfqpoint{{{}pt}}{{{}pt}}}}\n\\pgfusepath{{use as bounding box}}",
+        number(width),
+        number(height)
+    )
+    .unwrap();
+    renderer.render_children(&root, matrix, &Style::default().with(&root)?)?;
+    renderer.output.push_str("\\end{pgfpicture}");
+
+    Ok(renderer.output)
+}
+
+/// The size of a CSS pixel in TeX points, since there are 96 pixels to an inch
+const PT_PER_PX: f64 = 72.27 / 96.0;
+
+/// Convert a length, such as "12", "5mm" or "50%", to pixels
+fn length(value: &str, reference: f64) -> Result<f64, String> {
+    let value = value.trim();
+    let units = [
+        ("%", reference / 100.0),
+        ("px", 1.0),
+        ("pt", 96.0 / 72.0),
+        ("pc", 16.0),
+        ("mm", 96.0 / 25.4),
+        ("cm", 96.0 / 2.54),
+        ("in", 96.0),
+    ];
+    let (number, factor) = units
+        .iter()
+        .find_map(|(unit, factor)| value.strip_suffix(unit).map(|number| (number, *factor)))
+        .unwrap_or((value, 1.0));
+
+    number
+        .trim()
+        .parse::<f64>()
+        .ok()
+        .filter(|number| number.is_finite())
+        .map(|number| number * factor)
+        .ok_or_else(|| format!("invalid length '{value}'"))
+}
+
+/// Format a number for PGF, with no more decimals than needed
+fn number(value: f64) -> String {
+    let text = format!("{value:.3}");
+    let text = text.trim_end_matches('0').trim_end_matches('.');
+    match text {
+        "-0" | "" => "0".to_string(),
+        text => text.to_string(),
+    }
+}
+
+struct Element {
+    name: String,
+    attributes: HashMap<String, String>,
+    children: Vec<Node>,
+}
+
+enum Node {
+    Element(Element),
+    Text(String),
+}
+
+impl Element {
+    /// The text within the element and its children
+    fn text(&self) -> String {
+        self.children
+            .iter()
+            .map(|child| match child {
+                Node::Element(element) => element.text(),
+                Node::Text(text) => text.clone(),
+            })
+            .collect()
+    }
+
+    fn elements(&self) -> impl Iterator<Item = &Element> {
+        self.children.iter().filter_map(|child| match child {
+            Node::Element(element) => Some(element),
+            Node::Text(_) => None,
+        })
+    }
+
+    fn number(&self, name: &str, reference: f64) -> Result<f64, String> {
+        match self.attributes.get(name) {
+            Some(value) => length(value, reference),
+            None => Ok(0.0),
+        }
+    }
+}
+
+/// Parse the elements of an XML document, leaving out comments, processing
+/// instructions and the doctype
+fn parse_xml(text: &str) -> Result<Element, String> {
+    let mut stack: Vec<Element> = Vec::new();
+    let mut rest = text;
+
+    loop {
+        let Some(start) = rest.find('<') else {
+            return Err("the image ends before the <svg> element is closed".to_string());
+        };
+        if let Some(parent) = stack.last_mut() {
+            if !rest[..start].trim().is_empty() {
+                parent
+                    .children
+                    .push(Node::Text(unescape_xml(&rest[..start])));
+            }
+        }
+        rest = &rest[start..];
+
+        let skip_past = |rest: &str, end: &str| -> Result<usize, String> {
+            rest.find(end)
+                .map(|index| index + end.len())
+                .ok_or_else(|| format!("'{end}' is missing"))
+        };
+
+        if rest.starts_with("<!--") {
+            rest = &rest[skip_past(rest, "-->")?..];
+        } else if let Some(data) = rest.strip_prefix("<![CDATA[") {
+            let end = data.find("]]>").ok_or("']]>' is missing")?;
+            if let Some(parent) = stack.last_mut() {
+                parent.children.push(Node::Text(data[..end].to_string()));
+            }
+            rest = &data[end + 3..];
+        } else if rest.starts_with("<?") {
+            rest = &rest[skip_past(rest, "?>")?..];
+        } else if rest.starts_with("<!") {
+            rest = &rest[skip_past(rest, ">")?..];
+        } else if rest.starts_with("</") {
+            rest = &rest[skip_past(rest, ">")?..];
+            let element = stack.pop().ok_or("a closing tag has no opening tag")?;
+            match stack.last_mut() {
+                Some(parent) => parent.children.push(Node::Element(element)),
+                None => return Ok(element),
+            }
+        } else {
+            let (element, closed, length) = parse_tag(rest)?;
+            rest = &rest[length..];
+            if !closed {
+                stack.push(element);
+            } else {
+                match stack.last_mut() {
+                    Some(parent) => parent.children.push(Node::Element(element)),
+                    None => return Ok(element),
+                }
+            }
+        }
+    }
+}
+
+/// Parse an opening tag, and return the element, whether it is closed by
+/// `/>` and the length of the tag
+fn parse_tag(text: &str) -> Result<(Element, bool, usize), String> {
+    let is_name_end = |c: char| c.is_whitespace() || c == '/' || c == '>' || c == '=';
+    let mut index = 1;
+    let name_end = text[index..]
+        .find(is_name_end)
+        .ok_or("a tag is never closed")?
+        + index;
+    let name = text[index..name_end].to_string();
+    index = name_end;
+
+    let mut attributes = HashMap::new();
+    loop {
+        index += text[index..].len() - text[index..].trim_start().len();
+        let rest = &text[index..];
+        if rest.starts_with("/>") {
+            index += 2;
+            break;
+        } else if rest.starts_with('>') {
+            index += 1;
+            let element = Element {
+                name: local_name(name),
+                attributes,
+                children: Vec::new(),
+            };
+            return Ok((element, false, index));
+        } else if rest.is_empty() {
+            return Err(format!("the tag <{name}> is never closed"));
+        }
+
+        let key_end = rest.find(is_name_end).unwrap_or(rest.len());
+        let key = rest[..key_end].to_string();
+        let rest = rest[key_end..].trim_start();
+        let rest = rest
+            .strip_prefix('=')
+            .ok_or_else(|| format!("the attribute '{key}' has no value"))?
+            .trim_start();
+        let quote = rest
+            .chars()
+            .next()
+            .filter(|c| *c == '"' || *c == '\'')
+            .ok_or_else(|| format!("the value of '{key}' is not quoted"))?;
+        let value_end = rest[1..]
+            .find(quote)
+            .ok_or_else(|| format!("the value of '{key}' is never closed"))?
+            + 1;
+        attributes.insert(key, unescape_xml(&rest[1..value_end]));
+        index = text.len() - rest.len() + value_end + 1;
+    }
+
+    let element = Element {
+        name: local_name(name),
+        attributes,
+        children: Vec::new(),
+    };
+    Ok((element, true, index))
+}
+
+/// The name of an element in the SVG namespace, such as "path" for
+/// "svg:path". Elements of other namespaces keep their prefix and are left out.
+fn local_name(name: String) -> String {
+    match name.strip_prefix("svg:") {
+        Some(name) => name.to_string(),
+        None => name,
+    }
+}
+
+fn unescape_xml(text: &str) -> String {
+    let mut result = String::new();
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        result.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let Some(end) = rest.find(';') else {
+            break;
+        };
+        let entity = &rest[1..end];
+        let character = match entity {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            _ => entity
+                .strip_prefix("#x")
+                .map(|hex| u32::from_str_radix(hex, 16))
+                .or_else(|| entity.strip_prefix('#').map(str::parse::<u32>))
+                .and_then(Result::ok)
+                .and_then(char::from_u32),
+        };
+        match character {
+            Some(character) => {
+                result.push(character);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                result.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    result.push_str(rest);
+    result
+}
+
+fn collect_clip_paths<'a>(element: &'a Element, clip_paths: &mut HashMap<String, &'a Element>) {
+    for child in element.elements() {
+        if child.name == "clipPath" {
+            if let Some(id) = child.attributes.get("id") {
+                clip_paths.insert(id.clone(), child);
+            }
+        }
+        collect_clip_paths(child, clip_paths);
+    }
+}
+
+/// An affine transformation `[a b c d e f]`, like the SVG `matrix()`
+#[derive(Clone, Copy)]
+struct Matrix([f64; 6]);
+
+impl Matrix {
+    const IDENTITY: Matrix = Matrix([1.0, 0.0, 0.0, 1.0, 0.0, 0.0]);
+
+    /// The transformation that applies `other` first and then `self`
+    fn then(&self, other: &Matrix) -> Matrix {
+        let [a, b, c, d, e, f] = self.0;
+        let [g, h, i, j, k, l] = other.0;
+        Matrix([
+            a * g + c * h,
+            b * g + d * h,
+            a * i + c * j,
+            b * i + d * j,
+            a * k + c * l + e,
+            b * k + d * l + f,
+        ])
+    }
+
+    fn apply(&self, (x, y): (f64, f64)) -> (f64, f64) {
+        let [a, b, c, d, e, f] = self.0;
+        (a * x + c * y + e, b * x + d * y + f)
+    }
+
+    /// How much lengths are scaled, on average
+    fn scale(&self) -> f64 {
+        let [a, b, c, d, _, _] = self.0;
+        (a * d - b * c).abs().sqrt()
+    }
+
+    /// Parse a transform attribute, such as "translate(10,20) scale(2)"
+    fn parse(text: &str) -> Result<Matrix, String> {
+        let mut matrix = Matrix::IDENTITY;
+        let mut rest = text.trim();
+
+        while !rest.is_empty() {
+            let open = rest
+                .find('(')
+                .ok_or_else(|| format!("invalid transform '{text}'"))?;
+            let close = rest
+                .find(')')
+                .ok_or_else(|| format!("invalid transform '{text}'"))?;
+            let name = rest[..open].trim().trim_start_matches(',').trim();
+            let values = parse_numbers(&rest[open + 1..close])?;
+
+            let transform = match (name, values.as_slice()) {
+                ("matrix", &[a, b, c, d, e, f]) => Matrix([a, b, c, d, e, f]),
+                ("translate", &[x]) => Matrix([1.0, 0.0, 0.0, 1.0, x, 0.0]),
+                ("translate", &[x, y]) => Matrix([1.0, 0.0, 0.0, 1.0, x, y]),
+                ("scale", &[s]) => Matrix([s, 0.0, 0.0, s, 0.0, 0.0]),
+                ("scale", &[x, y]) => Matrix([x, 0.0, 0.0, y, 0.0, 0.0]),
+                ("rotate", &[angle]) => rotation(angle),
+                ("rotate", &[angle, x, y]) => Matrix([1.0, 0.0, 0.0, 1.0, x, y])
+                    .then(&rotation(angle))
+                    .then(&Matrix([1.0, 0.0, 0.0, 1.0, -x, -y])),
+                ("skewX", &[angle]) => Matrix([1.0, 0.0, angle.to_radians().tan(), 1.0, 0.0, 0.0]),
+                ("skewY", &[angle]) => Matrix([1.0, angle.to_radians().tan(), 0.0, 1.0, 0.0, 0.0]),
+                _ => return Err(format!("invalid transform '{text}'")),
+            };
+            matrix = matrix.then(&transform);
+            rest = rest[close + 1..].trim_start();
+        }
+
+        Ok(matrix)
+    }
+}
+
+fn rotation(angle: f64) -> Matrix {
+    let (sin, cos) = angle.to_radians().sin_cos();
+    Matrix([cos, sin, -sin, cos, 0.0, 0.0])
+}
+
+/// The presentation attributes that apply to an element, most of which are
+/// inherited from its parents
+#[derive(Clone)]
+struct Style {
+    fill: Option<String>,
+    stroke: Option<String>,
+    stroke_width: f64,
+    even_odd: bool,
+    fill_opacity: f64,
+    stroke_opacity: f64,
+    /// The opacity of the element and its parents multiplied, since groups
+    /// aren't drawn on their own
+    opacity: f64,
+    line_cap: String,
+    line_join: String,
+    miter_limit: f64,
+    dash_array: Vec<f64>,
+    font_size: f64,
+    bold: bool,
+    text_anchor: String,
+    visible: bool,
+}
+
+impl Default for Style {
+    fn default() -> Self {
+        Self {
+            fill: Some("000000".to_string()),
+            stroke: None,
+            stroke_width: 1.0,
+            even_odd: false,
+            fill_opacity: 1.0,
+            stroke_opacity: 1.0,
+            opacity: 1.0,
+            line_cap: "butt".to_string(),
+            line_join: "miter".to_string(),
+            miter_limit: 4.0,
+            dash_array: Vec::new(),
+            font_size: 16.0,
+            bold: false,
+            text_anchor: "start".to_string(),
+            visible: true,
+        }
+    }
+}
+
+impl Style {
+    /// The style of an element, given the style of its parent. Declarations
+    /// in the style attribute take precedence over the other attributes.
+    fn with(&self, element: &Element) -> Result<Style, String> {
+        let mut style = self.clone();
+        style.visible = true;
+
+        for (name, value) in &element.attributes {
+            style.set(name, value)?;
+        }
+        if let Some(declarations) = element.attributes.get("style") {
+            for declaration in declarations.split(';') {
+                if let Some((name, value)) = declaration.split_once(':') {
+                    style.set(name.trim(), value.trim())?;
+                }
+            }
+        }
+
+        Ok(style)
+    }
+
+    fn set(&mut self, name: &str, value: &str) -> Result<(), String> {
+        let value = value.trim();
+        let number = |value: &str| {
+            value
+                .trim_end_matches("px")
+                .parse::<f64>()
+                .map_err(|_| format!("invalid value '{value}' of '{name}'"))
+        };
+
+        match name {
+            "fill" => self.fill = parse_paint(value)?,
+            "stroke" => self.stroke = parse_paint(value)?,
+            "stroke-width" => self.stroke_width = number(value)?,
+            "fill-rule" => self.even_odd = value == "evenodd",
+            "fill-opacity" => self.fill_opacity = number(value)?,
+            "stroke-opacity" => self.stroke_opacity = number(value)?,
+            "opacity" => self.opacity *= number(value)?,
+            "stroke-linecap" => self.line_cap = value.to_string(),
+            "stroke-linejoin" => self.line_join = value.to_string(),
+            "stroke-miterlimit" => self.miter_limit = number(value)?,
+            "stroke-dasharray" if value == "none" => self.dash_array.clear(),
+            "stroke-dasharray" => self.dash_array = parse_numbers(value)?,
+            "font-size" => self.font_size = length(value, self.font_size)?,
+            "font-weight" => {
+                self.bold =
+                    value == "bold" || value.parse::<u32>().is_ok_and(|weight| weight >= 600)
+            }
+            "text-anchor" => self.text_anchor = value.to_string(),
+            "display" => self.visible &= value != "none",
+            "visibility" => self.visible = value == "visible",
+            "mask" | "filter" if value != "none" => {
+                return Err(format!("'{name}' is not supported"))
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+}
+
+/// Parse a colour as six hexadecimal digits, or `None` for "none"
+fn parse_paint(value: &str) -> Result<Option<String>, String> {
+    let value = value.trim().to_lowercase();
+    let hex = match value.as_str() {
+        "none" | "transparent" => return Ok(None),
+        "black" | "currentcolor" => "000000".to_string(),
+        "white" => "ffffff".to_string(),
+        "red" => "ff0000".to_string(),
+        "green" => "008000".to_string(),
+        "blue" => "0000ff".to_string(),
+        "yellow" => "ffff00".to_string(),
+        "gray" | "grey" => "808080".to_string(),
+        "silver" => "c0c0c0".to_string(),
+        "orange" => "ffa500".to_string(),
+        "purple" => "800080".to_string(),
+        "navy" => "000080".to_string(),
+        "maroon" => "800000".to_string(),
+        "olive" => "808000".to_string(),
+        "teal" => "008080".to_string(),
+        "lime" => "00ff00".to_string(),
+        "aqua" | "cyan" => "00ffff".to_string(),
+        "fuchsia" | "magenta" => "ff00ff".to_string(),
+        _ if value.starts_with("url(") => {
+            return Err("gradients and patterns are not supported".to_string())
+        }
+        _ if value.starts_with("rgb(") && value.ends_with(')') => {
+            let components = value[4..value.len() - 1]
+                .split(',')
+                .map(|component| {
+                    let component = component.trim();
+                    match component.strip_suffix('%') {
+                        Some(percent) => percent.parse::<f64>().map(|p| p * 2.55),
+                        None => component.parse::<f64>(),
+                    }
+                })
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|_| format!("invalid colour '{value}'"))?;
+            let [r, g, b] = components[..] else {
+                return Err(format!("invalid colour '{value}'"));
+            };
+            format!(
+                "{:02x}{:02x}{:02x}",
+                r.clamp(0.0, 255.0).round() as u8,
+                g.clamp(0.0, 255.0).round() as u8,
+                b.clamp(0.0, 255.0).round() as u8
+            )
+        }
+        _ => match value.strip_prefix('#') {
+            Some(hex) if hex.len() == 3 => hex.chars().flat_map(|c| [c, c]).collect(),
+            Some(hex) if hex.len() == 6 => hex.to_string(),
+            _ => return Err(format!("the colour '{value}' is not supported")),
+        },
+    };
+
+    if hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        Ok(Some(hex.to_uppercase()))
+    } else {
+        Err(format!("invalid colour '{value}'"))
+    }
+}
+
+/// A segment of a path, with absolute coordinates
+enum Segment {
+    Move(f64, f64),
+    Line(f64, f64),
+    Curve(f64, f64, f64, f64, f64, f64),
+    Close,
+}
+
+/// Parse numbers separated by whitespace or commas
+fn parse_numbers(text: &str) -> Result<Vec<f64>, String> {
+    let mut parser = PathParser {
+        text: text.as_bytes(),
+        index: 0,
+    };
+    let mut numbers = Vec::new();
+    while parser.at_number() {
+        numbers.push(parser.number()?);
+    }
+    if parser.index < text.len() {
+        return Err(format!("invalid numbers '{text}'"));
+    }
+    Ok(numbers)
+}
+
+struct PathParser<'a> {
+    text: &'a [u8],
+    index: usize,
+}
+
+impl PathParser<'_> {
+    fn skip_separators(&mut self) {
+        while self
+            .text
+            .get(self.index)
+            .is_some_and(|c| c.is_ascii_whitespace() || *c == b',')
+        {
+            self.index += 1;
+        }
+    }
+
+    fn at_number(&mut self) -> bool {
+        self.skip_separators();
+        self.text
+            .get(self.index)
+            .is_some_and(|c| c.is_ascii_digit() || matches!(c, b'.' | b'-' | b'+'))
+    }
+
+    /// Parse a number, which ends where another one starts in "1.5.5" or "1-2"
+    fn number(&mut self) -> Result<f64, String> {
+        self.skip_separators();
+        let start = self.index;
+        let mut seen_dot = false;
+        let mut seen_exponent = false;
+
+        while let Some(&c) = self.text.get(self.index) {
+            let is_sign = c == b'-' || c == b'+';
+            let after_exponent =
+                self.index > start && matches!(self.text[self.index - 1], b'e' | b'E');
+            let part_of_number = c.is_ascii_digit()
+                || (is_sign && (self.index == start || after_exponent))
+                || (c == b'.' && !seen_dot && !seen_exponent)
+                || (matches!(c, b'e' | b'E') && !seen_exponent && self.index > start);
+            if !part_of_number {
+                break;
+            }
+            seen_dot |= c == b'.';
+            seen_exponent |= matches!(c, b'e' | b'E');
+            self.index += 1;
+        }
+
+        std::str::from_utf8(&self.text[start..self.index])
+            .unwrap()
+            .parse()
+            .ok()
+            .filter(|number: &f64| number.is_finite())
+            .ok_or_else(|| "invalid number in path data".to_string())
+    }
+
+    /// Parse the flag of an arc, which may be written without a separator
+    fn flag(&mut self) -> Result<bool, String> {
+        self.skip_separators();
+        let flag = match self.text.get(self.index) {
+            Some(b'0') => false,
+            Some(b'1') => true,
+            _ => return Err("invalid arc flag in path data".to_string()),
+        };
+        self.index += 1;
+        Ok(flag)
+    }
+}
+
+/// Parse path data into segments, where quadratic curves and arcs are
+/// converted into cubic curves
+fn parse_path(data: &str) -> Result<Vec<Segment>, String> {
+    let mut parser = PathParser {
+        text: data.as_bytes(),
+        index: 0,
+    };
+    let mut segments = Vec::new();
+    let mut current = (0.0, 0.0);
+    let mut start = (0.0, 0.0);
+    // the last control point, which smooth curves are reflected from
+    let mut last_cubic: Option<(f64, f64)> = None;
+    let mut last_quadratic: Option<(f64, f64)> = None;
+    let mut command = b' ';
+
+    loop {
+        parser.skip_separators();
+        match parser.text.get(parser.index) {
+            None => break,
+            Some(c) if c.is_ascii_alphabetic() => {
+                if segments.is_empty() && !c.eq_ignore_ascii_case(&b'M') {
+                    return Err("path data must start with a move".to_string());
+                }
+                command = *c;
+                parser.index += 1;
+            }
+            // numbers without a command repeat the previous one, where a
+            // move is followed by lines
+            Some(_) => {
+                command = match command {
+                    b'M' => b'L',
+                    b'm' => b'l',
+                    b' ' | b'Z' | b'z' => {
+                        return Err("path data must start with a move".to_string())
+                    }
+                    command => command,
+                }
+            }
+        }
+
+        let relative = command.is_ascii_lowercase();
+        let (dx, dy) = if relative { current } else { (0.0, 0.0) };
+        let point = |parser: &mut PathParser| -> Result<(f64, f64), String> {
+            Ok((parser.number()? + dx, parser.number()? + dy))
+        };
+
+        let (mut cubic, mut quadratic) = (None, None);
+        match command.to_ascii_uppercase() {
+            b'M' => {
+                current = point(&mut parser)?;
+                start = current;
+                segments.push(Segment::Move(current.0, current.1));
+            }
+            b'L' => {
+                current = point(&mut parser)?;
+                segments.push(Segment::Line(current.0, current.1));
+            }
+            b'H' => {
+                current.0 = parser.number()? + dx;
+                segments.push(Segment::Line(current.0, current.1));
+            }
+            b'V' => {
+                current.1 = parser.number()? + dy;
+                segments.push(Segment::Line(current.0, current.1));
+            }
+            b'C' | b'S' => {
+                let first = if command.eq_ignore_ascii_case(&b'C') {
+                    point(&mut parser)?
+                } else {
+                    reflect(last_cubic, current)
+                };
+                let second = point(&mut parser)?;
+                current = point(&mut parser)?;
+                segments.push(Segment::Curve(
+                    first.0, first.1, second.0, second.1, current.0, current.1,
+                ));
+                cubic = Some(second);
+            }
+            b'Q' | b'T' => {
+                let control = if command.eq_ignore_ascii_case(&b'Q') {
+                    point(&mut parser)?
+                } else {
+                    reflect(last_quadratic, current)
+                };
+                let end = point(&mut parser)?;
+                segments.push(quadratic_to_cubic(current, control, end));
+                current = end;
+                quadratic = Some(control);
+            }
+            b'A' => {
+                let (rx, ry) = (parser.number()?, parser.number()?);
+                let rotation = parser.number()?;
+                let (large_arc, sweep) = (parser.flag()?, parser.flag()?);
+                let end = point(&mut parser)?;
+                segments.extend(arc_to_curves(
+                    current,
+                    (rx, ry),
+                    rotation,
+                    large_arc,
+                    sweep,
+                    end,
+                ));
+                current = end;
+            }
+            b'Z' => {
+                segments.push(Segment::Close);
+                current = start;
+            }
+            _ => return Err(format!("unknown path command '{}'", char::from(command))),
+        }
+        last_cubic = cubic;
+        last_quadratic = quadratic;
+    }
+
+    Ok(segments)
+}
+
+/// The control point of a smooth curve, reflected from the previous one
+fn reflect(control: Option<(f64, f64)>, (x, y): (f64, f64)) -> (f64, f64) {
+    match control {
+        Some((cx, cy)) => (2.0 * x - cx, 2.0 * y - cy),
+        None => (x, y),
+    }
+}
+
+fn quadratic_to_cubic(start: (f64, f64), control: (f64, f64), end: (f64, f64)) -> Segment {
+    let towards = |from: (f64, f64)| {
+        (
+            from.0 + 2.0 / 3.0 * (control.0 - from.0),
+            from.1 + 2.0 / 3.0 * (control.1 - from.1),
+        )
+    };
+    let (first, second) = (towards(start), towards(end));
+    Segment::Curve(first.0, first.1, second.0, second.1, end.0, end.1)
+}
+
+/// Convert an elliptical arc into cubic curves of at most a quarter turn
+/// each, following the implementation notes of the SVG specification
+fn arc_to_curves(
+    start: (f64, f64),
+    (rx, ry): (f64, f64),
+    rotation: f64,
+    large_arc: bool,
+    sweep: bool,
+    end: (f64, f64),
+) -> Vec<Segment> {
+    let (mut rx, mut ry) = (rx.abs(), ry.abs());
+    if rx == 0.0 || ry == 0.0 || start == end {
+        return vec![Segment::Line(end.0, end.1)];
+    }
+
+    let (sin, cos) = rotation.to_radians().sin_cos();
+    let (hx, hy) = ((start.0 - end.0) / 2.0, (start.1 - end.1) / 2.0);
+    let (x1, y1) = (cos * hx + sin * hy, -sin * hx + cos * hy);
+
+    // radii that are too small are scaled up until the arc fits
+    let lambda = (x1 * x1) / (rx * rx) + (y1 * y1) / (ry * ry);
+    if lambda > 1.0 {
+        rx *= lambda.sqrt();
+        ry *= lambda.sqrt();
+    }
+
+    let numerator = rx * rx * ry * ry - rx * rx * y1 * y1 - ry * ry * x1 * x1;
+    let denominator = rx * rx * y1 * y1 + ry * ry * x1 * x1;
+    let sign = if large_arc == sweep { -1.0 } else { 1.0 };
+    let coefficient = sign * (numerator / denominator).max(0.0).sqrt();
+    let (cx1, cy1) = (coefficient * rx * y1 / ry, -coefficient * ry * x1 / rx);
+    let center = (
+        cos * cx1 - sin * cy1 + (start.0 + end.0) / 2.0,
+        sin * cx1 + cos * cy1 + (start.1 + end.1) / 2.0,
+    );
+
+    let angle =
+        |(ux, uy): (f64, f64), (vx, vy): (f64, f64)| (ux * vy - uy * vx).atan2(ux * vx + uy * vy);
+    let from = ((x1 - cx1) / rx, (y1 - cy1) / ry);
+    let to = ((-x1 - cx1) / rx, (-y1 - cy1) / ry);
+    let theta = angle((1.0, 0.0), from);
+    let mut delta = angle(from, to);
+    if !sweep && delta > 0.0 {
+        delta -= 2.0 * PI;
+    } else if sweep && delta < 0.0 {
+        delta += 2.0 * PI;
+    }
+
+    let count = (delta.abs() / (PI / 2.0)).ceil().max(1.0);
+    let step = delta / count;
+    let handle = 4.0 / 3.0 * (step / 4.0).tan();
+    let on_ellipse = |(ux, uy): (f64, f64)| {
+        (
+            center.0 + rx * ux * cos - ry * uy * sin,
+            center.1 + rx * ux * sin + ry * uy * cos,
+        )
+    };
+
+    (0..count as usize)
+        .map(|index| {
+            let a = theta + step * index as f64;
+            let b = a + step;
+            let first = on_ellipse((a.cos() - handle * a.sin(), a.sin() + handle * a.cos()));
+            let second = on_ellipse((b.cos() + handle * b.sin(), b.sin() - handle * b.cos()));
+            let end = if index + 1 == count as usize {
+                end
+            } else {
+                on_ellipse((b.cos(), b.sin()))
+            };
+            Segment::Curve(first.0, first.1, second.0, second.1, end.0, end.1)
+        })
+        .collect()
+}
+
+/// A closed ellipse, made out of two arcs
+fn ellipse(cx: f64, cy: f64, rx: f64, ry: f64) -> Vec<Segment> {
+    let (left, right) = ((cx - rx, cy), (cx + rx, cy));
+    let mut segments = vec![Segment::Move(right.0, right.1)];
+    segments.extend(arc_to_curves(right, (rx, ry), 0.0, false, true, left));
+    segments.extend(arc_to_curves(left, (rx, ry), 0.0, false, true, right));
+    segments.push(Segment::Close);
+    segments
+}
+
+/// Writes the PGF commands of the elements of an image
+struct Renderer<'a> {
+    clip_paths: HashMap<String, &'a Element>,
+    /// The size of the viewBox, which percentages refer to
+    viewport: (f64, f64),
+    output: String,
+    /// The graphics state that has been set, such as the fill colour, so that
+    /// it is only set again when it changes
+    state: HashMap<&'static str, String>,
+    /// The graphics state outside of each `pgfscope`
+    saved: Vec<HashMap<&'static str, String>>,
+}
+
+impl Renderer<'_> {
+    fn render_children(
+        &mut self,
+        element: &Element,
+        matrix: Matrix,
+        style: &Style,
+    ) -> Result<(), String> {
+        for child in element.elements() {
+            self.render(child, matrix, style)?;
+        }
+        Ok(())
+    }
+
+    fn render(&mut self, element: &Element, matrix: Matrix, style: &Style) -> Result<(), String> {
+        let style = style.with(element)?;
+        if !style.visible {
+            return Ok(());
+        }
+        let matrix = match element.attributes.get("transform") {
+            Some(transform) => matrix.then(&Matrix::parse(transform)?),
+            None => matrix,
+        };
+
+        let clip_path = match element.attributes.get("clip-path").map(String::as_str) {
+            None | Some("none") => None,
+            Some(url) => {
+                let id = url
+                    .trim()
+                    .strip_prefix("url(#")
+                    .and_then(|id| id.strip_suffix(')'))
+                    .ok_or_else(|| format!("invalid clip path '{url}'"))?;
+                let clip_path = *self
+                    .clip_paths
+                    .get(id)
+                    .ok_or_else(|| format!("the clip path '{id}' is missing"))?;
+                Some(clip_path)
+            }
+        };
+        if let Some(clip_path) = clip_path {
+            self.begin_scope();
+            for child in clip_path.elements() {
+                let child_matrix = match child.attributes.get("transform") {
+                    Some(transform) => matrix.then(&Matrix::parse(transform)?),
+                    None => matrix,
+                };
+                if let Some(segments) = self.shape(child)? {
+                    self.write_path(&segments, child_matrix);
+                }
+            }
+            self.output.push_str("\\pgfusepath{clip}\n");
+        }
+
+        match element.name.as_str() {
+            "g" | "a" | "switch" => self.render_children(element, matrix, &style)?,
+            "text" => self.text(element, matrix, &style)?,
+            "image" | "use" | "foreignObject" | "pattern" | "mask" | "filter" | "svg" => {
+                return Err(format!("<{}> is not supported", element.name))
+            }
+            "style" if !element.text().trim().is_empty() => {
+                return Err("style sheets are not supported".to_string())
+            }
+            _ => {
+                if let Some(segments) = self.shape(element)? {
+                    self.draw(&segments, matrix, &style);
+                }
+            }
+        }
+
+        if clip_path.is_some() {
+            self.end_scope();
+        }
+        Ok(())
+    }
+
+    /// The outline of a basic shape or path, or `None` for elements that
+    /// aren't drawn, such as `<defs>` and `<title>`
+    fn shape(&self, element: &Element) -> Result<Option<Vec<Segment>>, String> {
+        let (width, height) = self.viewport;
+        let diagonal = (width * width + height * height).sqrt() / 2.0_f64.sqrt();
+        let get = |name: &str, reference: f64| element.number(name, reference);
+
+        let segments = match element.name.as_str() {
+            "path" => match element.attributes.get("d") {
+                Some(data) => parse_path(data)?,
+                None => Vec::new(),
+            },
+            "rect" => {
+                let (x, y) = (get("x", width)?, get("y", height)?);
+                let (w, h) = (get("width", width)?, get("height", height)?);
+                let (rx, ry) = match (element.attributes.get("rx"), element.attributes.get("ry")) {
+                    (None, None) => (0.0, 0.0),
+                    (Some(_), None) => (get("rx", width)?, get("rx", width)?),
+                    (None, Some(_)) => (get("ry", height)?, get("ry", height)?),
+                    (Some(_), Some(_)) => (get("rx", width)?, get("ry", height)?),
+                };
+                let (rx, ry) = (rx.min(w / 2.0), ry.min(h / 2.0));
+
+                if rx <= 0.0 || ry <= 0.0 {
+                    vec![
+                        Segment::Move(x, y),
+                        Segment::Line(x + w, y),
+                        Segment::Line(x + w, y + h),
+                        Segment::Line(x, y + h),
+                        Segment::Close,
+                    ]
+                } else {
+                    let mut segments = vec![Segment::Move(x + rx, y)];
+                    let corners = [
+                        ((x + w - rx, y), (x + w, y + ry)),
+                        ((x + w, y + h - ry), (x + w - rx, y + h)),
+                        ((x + rx, y + h), (x, y + h - ry)),
+                        ((x, y + ry), (x + rx, y)),
+                    ];
+                    for (line_end, corner_end) in corners {
+                        segments.push(Segment::Line(line_end.0, line_end.1));
+                        segments.extend(arc_to_curves(
+                            line_end,
+                            (rx, ry),
+                            0.0,
+                            false,
+                            true,
+                            corner_end,
+                        ));
+                    }
+                    segments.push(Segment::Close);
+                    segments
+                }
+            }
+            "circle" => {
+                let r = get("r", diagonal)?;
+                ellipse(get("cx", width)?, get("cy", height)?, r, r)
+            }
+            "ellipse" => ellipse(
+                get("cx", width)?,
+                get("cy", height)?,
+                get("rx", width)?,
+                get("ry", height)?,
+            ),
+            "line" => vec![
+                Segment::Move(get("x1", width)?, get("y1", height)?),
+                Segment::Line(get("x2", width)?, get("y2", height)?),
+            ],
+            "polyline" | "polygon" => {
+                let points = parse_numbers(element.attributes.get("points").map_or("", |p| p))?;
+                let mut segments: Vec<Segment> = points
+                    .chunks_exact(2)
+                    .enumerate()
+                    .map(|(index, point)| match index {
+                        0 => Segment::Move(point[0], point[1]),
+                        _ => Segment::Line(point[0], point[1]),
+                    })
+                    .collect();
+                if element.name == "polygon" {
+                    segments.push(Segment::Close);
+                }
+                segments
+            }
+            _ => return Ok(None),
+        };
+
+        Ok(Some(segments))
+    }
+
+    fn write_path(&mut self, segments: &[Segment], matrix: Matrix) {
+        let point = |x: f64, y: f64| {
+            let (x, y) = matrix.apply((x, y));
+            format!("{{{}pt}}{{{}pt}}", number(x), number(y))
+        };
+
+        for segment in segments {
+            match *segment {
+                Segment::Move(x, y) => write!(self.output, "\\pgfpathqmoveto{}", point(x, y)),
+                Segment::Line(x, y) => write!(self.output, "\\pgfpathqlineto{}", point(x, y)),
+                Segment::Curve(x1, y1, x2, y2, x, y) => write!(
+                    self.output,
+                    "\\pgfpathqcurveto{}{}{}",
+                    point(x1, y1),
+                    point(x2, y2),
+                    point(x, y)
+                ),
+                Segment::Close => write!(self.output, "\\pgfpathclose"),
+            }
+            .unwrap();
+        }
+        self.output.push('\n');
+    }
+
+    /// Fill and stroke a path
+    fn draw(&mut self, segments: &[Segment], matrix: Matrix, style: &Style) {
+        let fill = style.fill.as_ref().filter(|_| style.fill_opacity > 0.0);
+        let stroke = style
+            .stroke
+            .as_ref()
+            .filter(|_| style.stroke_width > 0.0 && style.stroke_opacity > 0.0);
+        if segments.is_empty() || (fill.is_none() && stroke.is_none()) {
+            return;
+        }
+
+        let mut actions = Vec::new();
+        if let Some(fill) = fill {
+            self.set(
+                "fill",
+                format!("\\definecolor{{svgfill}}{{HTML}}{{{fill}}}\\pgfsetfillcolor{{svgfill}}"),
+            );
+            self.set(
+                "fill opacity",
+                format!(
+                    "\\pgfsetfillopacity{{{}}}",
+                    number(style.fill_opacity * style.opacity)
+                ),
+            );
+            let rule = if style.even_odd {
+                "\\pgfseteorule"
+            } else {
+                "\\pgfsetnonzerorule"
+            };
+            self.set("rule", rule.to_string());
+            actions.push("fill");
+        }
+        if let Some(stroke) = stroke {
+            // the matrix scales lengths into points
+            let width = style.stroke_width * matrix.scale();
+            self.set("stroke", format!("\\definecolor{{svgstroke}}{{HTML}}{{{stroke}}}\\pgfsetstrokecolor{{svgstroke}}"));
+            self.set(
+                "stroke opacity",
+                format!(
+                    "\\pgfsetstrokeopacity{{{}}}",
+                    number(style.stroke_opacity * style.opacity)
+                ),
+            );
+            self.set(
+                "line width",
+                format!("\\pgfsetlinewidth{{{}pt}}", number(width)),
+            );
+            let cap = match style.line_cap.as_str() {
+                "round" => "\\pgfsetroundcap",
+                "square" => "\\pgfsetrectcap",
+                _ => "\\pgfsetbuttcap",
+            };
+            self.set("cap", cap.to_string());
+            let join = match style.line_join.as_str() {
+                "round" => "\\pgfsetroundjoin",
+                "bevel" => "\\pgfsetbeveljoin",
+                _ => "\\pgfsetmiterjoin",
+            };
+            self.set("join", join.to_string());
+            self.set(
+                "miter limit",
+                format!("\\pgfsetmiterlimit{{{}}}", number(style.miter_limit)),
+            );
+            let dashes: String = style
+                .dash_array
+                .iter()
+                .map(|dash| format!("{{{}pt}}", number(dash * matrix.scale())))
+                .collect();
+            self.set("dash", format!("\\pgfsetdash{{{dashes}}}{{0pt}}"));
+            actions.push("stroke");
+        }
+
+        self.write_path(segments, matrix);
+        writeln!(self.output, "\\pgfusepath{{{}}}", actions.join(",")).unwrap();
+    }
+
+    /// Write text, where the position of each line is given by the `<text>`
+    /// element, and the font is the font of the document
+    fn text(&mut self, element: &Element, matrix: Matrix, style: &Style) -> Result<(), String> {
+        let text = element.text();
+        let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+        if text.is_empty() {
+            return Ok(());
+        }
+
+        // only the first of several positions is used
+        let first = |name: &str, reference: f64| -> Result<f64, String> {
+            match element.attributes.get(name) {
+                Some(values) => values
+                    .split([' ', ','])
+                    .find(|value| !value.is_empty())
+                    .map_or(Ok(0.0), |value| length(value, reference)),
+                None => Ok(0.0),
+            }
+        };
+        let (x, y) = matrix.apply((first("x", self.viewport.0)?, first("y", self.viewport.1)?));
+        let [a, b, _, _, _, _] = matrix.0;
+        let rotation = b.atan2(a).to_degrees();
+        let size = style.font_size * matrix.scale();
+
+        let mut options = vec!["base".to_string()];
+        match style.text_anchor.as_str() {
+            "middle" => {}
+            "end" => options.push("right".to_string()),
+            _ => options.push("left".to_string()),
+        }
+        options.push(format!(
+            "at={{\\pgfqpoint{{{}pt}}{{{}pt}}}}",
+            number(x),
+            number(y)
+        ));
+        if rotation.abs() > 0.01 {
+            options.push(format!("rotate={}", number(rotation)));
+        }
+
+        let mut font = format!(
+            "\\fontsize{{{}}}{{{}}}\\selectfont",
+            number(size),
+            number(size * 1.2)
+        );
+        if style.bold {
+            font.push_str("\\bfseries");
+        }
+        if let Some(fill) = &style.fill {
+            write!(
+                font,
+                "\\definecolor{{svgtext}}{{HTML}}{{{fill}}}\\color{{svgtext}}"
+            )
+            .unwrap();
+        }
+        writeln!(
+            self.output,
+            "\\pgftext[{}]{{{font} {}}}",
+            options.join(","),
+            escape_latex(&text)
+        )
+        .unwrap();
+
+        Ok(())
+    }
+
+    fn set(&mut self, key: &'static str, command: String) {
+        if self.state.get(key) != Some(&command) {
+            self.output.push_str(&command);
+            self.output.push('\n');
+            self.state.insert(key, command);
+        }
+    }
+
+    fn begin_scope(&mut self) {
+        self.output.push_str("\\begin{pgfscope}\n");
+        self.saved.push(self.state.clone());
+    }
+
+    fn end_scope(&mut self) {
+        self.output.push_str("\\end{pgfscope}\n");
+        self.state = self.saved.pop().unwrap_or_default();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The segments written like path data, with rounded coordinates
+    fn path(data: &str) -> String {
+        let segments = parse_path(data).unwrap();
+        write_segments(&segments)
+    }
+
+    fn write_segments(segments: &[Segment]) -> String {
+        let numbers = |values: &[f64]| {
+            values
+                .iter()
+                .map(|value| number(*value))
+                .collect::<Vec<_>>()
+                .join(" ")
+        };
+        segments
+            .iter()
+            .map(|segment| match segment {
+                Segment::Move(x, y) => format!("M {}", numbers(&[*x, *y])),
+                Segment::Line(x, y) => format!("L {}", numbers(&[*x, *y])),
+                Segment::Curve(x1, y1, x2, y2, x, y) => {
+                    format!("C {}", numbers(&[*x1, *y1, *x2, *y2, *x, *y]))
+                }
+                Segment::Close => "Z".to_string(),
+            })
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    #[test]
+    fn parses_absolute_and_relative_commands() {
+        assert_eq!(
+            path("M10,20 L30 40 h5 V0 z"),
+            "M 10 20 L 30 40 L 35 40 L 35 0 Z"
+        );
+        assert_eq!(path("m1 1 2 2 l-1-1"), "M 1 1 L 3 3 L 2 2");
+        assert_eq!(path("M0 0 Q3 3 6 0"), "M 0 0 C 2 2 4 2 6 0");
+        assert_eq!(
+            path("M0 0 C0 1 1 1 1 0 S2 -1 2 0"),
+            "M 0 0 C 0 1 1 1 1 0 C 1 -1 2 -1 2 0"
+        );
+        assert_eq!(path(""), "");
+    }
+
+    #[test]
+    fn parses_compact_numbers() {
+        assert_eq!(path("M.5.5L1e1-2.5E-1"), "M 0.5 0.5 L 10 -0.25");
+        assert_eq!(path("M+1,+2"), "M 1 2");
+        assert_eq!(parse_numbers(" 1, 2\n3 ").unwrap(), [1.0, 2.0, 3.0]);
+    }
+
+    #[test]
+    fn rejects_malformed_path_data() {
+        let error = |data: &str| parse_path(data).err().unwrap();
+        assert_eq!(error("L1 1"), "path data must start with a move");
+        assert_eq!(error("10 10"), "path data must start with a move");
+        assert_eq!(error("M1 1 X2 2"), "unknown path command 'X'");
+        assert_eq!(error("M1"), "invalid number in path data");
+        assert_eq!(
+            error("M1 1 A1 1 0 2 0 3 3"),
+            "invalid arc flag in path data"
+        );
+        assert!(parse_numbers("1 two").is_err());
+    }
+
+    #[test]
+    fn rejects_non_finite_numbers() {
+        assert_eq!(
+            parse_path("M1e999 0").err().unwrap(),
+            "invalid number in path data"
+        );
+        assert!(parse_path("M NaN 0").is_err());
+        assert!(parse_path("M inf 0").is_err());
+        assert!(length("NaN", 100.0).is_err());
+        assert!(length("infpx", 100.0).is_err());
+        assert_eq!(length("50%", 300.0).unwrap(), 150.0);
+    }
+
+    #[test]
+    fn converts_arcs_into_curves() {
+        // a half circle from (0, 0) to (2, 0) with radius 1, below or above
+        // the line depending on the sweep flag
+        assert_eq!(
+            path("M0 0 A1 1 0 0 1 2 0"),
+            "M 0 0 C 0 -0.552 0.448 -1 1 -1 C 1.552 -1 2 -0.552 2 0"
+        );
+        assert_eq!(
+            path("M0 0 A1 1 0 0 0 2 0"),
+            "M 0 0 C 0 0.552 0.448 1 1 1 C 1.552 1 2 0.552 2 0"
+        );
+        // the flags may be written without separators
+        assert_eq!(path("M0 0a1 1 0 0 12 0"), path("M0 0 A1 1 0 0 1 2 0"));
+        // the large arc goes the long way around
+        assert_eq!(parse_path("M1 0 A1 1 0 1 1 0 1").unwrap().len(), 1 + 3);
+        // radii that are too small are scaled up
+        assert_eq!(path("M0 0 A0.1 0.1 0 0 1 2 0"), path("M0 0 A1 1 0 0 1 2 0"));
+        // a zero radius is a line
+        assert_eq!(path("M0 0 A0 1 0 0 1 2 0"), "M 0 0 L 2 0");
+    }
+
+    #[test]
+    fn reports_unsupported_features() {
+        assert_eq!(
+            svg_to_pgf("<html/>").err().unwrap(),
+            "the root element is <html> and not <svg>"
+        );
+        let error = svg_to_pgf(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="10" height="10"><filter id="f"/></svg>"#,
+        )
+        .err()
+        .unwrap();
+        assert_eq!(error, "<filter> is not supported");
+    }
+}
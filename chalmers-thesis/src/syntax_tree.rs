@@ -0,0 +1,355 @@
+use crate::{
+    chart::{FONT, PALETTE},
+    escape::{escape_html, escape_latex},
+    Error,
+};
+use serde_json::{json, Value};
+use std::{fmt::Write, iter::Peekable, str::Chars};
+
+/// Approximate width of a character, used to lay out the tree in HTML
+const CHAR_WIDTH: f64 = 7.5;
+const NODE_HEIGHT: f64 = 26.0;
+const LEVEL_HEIGHT: f64 = 64.0;
+const SIBLING_GAP: f64 = 14.0;
+const PADDING: f64 = 10.0;
+
+/// A node in a tree written in labelled bracket notation, such as
+/// `[Document [Paragraph [Text "hi"]]]`. Leaves written as quoted strings
+/// are kept apart from labelled nodes, since they are drawn differently.
+struct Node {
+    label: String,
+    quoted: bool,
+    children: Vec<Node>,
+}
+
+/// Transform `[syntax-tree]`, which draws a tree from labelled bracket notation
+pub(crate) fn transform_syntax_tree(input: Value, to: &str) -> Result<String, Error> {
+    let data = input["data"].as_str().unwrap();
+    let tree = parse_tree(data)?;
+
+    let json = match to {
+        "latex" => json!([
+            {"name": "set-add", "arguments": {"name": "imports"}, "data": "\\usepackage{forest}"},
+            format!("\\begin{{forest}}\nfor tree={{font=\\small, s sep=4mm}}\n{}\n\\end{{forest}}\n", to_forest(&tree)),
+        ]),
+        "html" => json!([to_svg(&tree)]),
+        other => {
+            eprintln!("Cannot convert syntax-tree to {other}");
+            json!([])
+        }
+    };
+
+    Ok(serde_json::to_string(&json).unwrap())
+}
+
+/// Parse a whole tree, which is a single node
+fn parse_tree(text: &str) -> Result<Node, Error> {
+    let mut chars = text.trim().chars().peekable();
+
+    skip_whitespace(&mut chars);
+    let tree = parse_node(&mut chars)?;
+    skip_whitespace(&mut chars);
+    if chars.peek().is_some() {
+        return Err(Error::SyntaxTree(
+            "Unexpected text after the end of the tree".to_string(),
+        ));
+    }
+
+    Ok(tree)
+}
+
+fn skip_whitespace(chars: &mut Peekable<Chars>) {
+    while chars.peek().is_some_and(|c| c.is_whitespace()) {
+        chars.next();
+    }
+}
+
+/// Parse a node, which is either `[label children...]`, a quoted string or a bare word
+fn parse_node(chars: &mut Peekable<Chars>) -> Result<Node, Error> {
+    match chars.peek() {
+        Some('[') => {
+            chars.next();
+            skip_whitespace(chars);
+            let (label, _) = parse_label(chars)?;
+
+            let mut children = Vec::new();
+            loop {
+                skip_whitespace(chars);
+                match chars.peek() {
+                    Some(']') => {
+                        chars.next();
+                        break;
+                    }
+                    Some(_) => children.push(parse_node(chars)?),
+                    None => {
+                        return Err(Error::SyntaxTree(format!(
+                            "Missing closing bracket for '{label}'"
+                        )))
+                    }
+                }
+            }
+
+            Ok(Node {
+                label,
+                quoted: false,
+                children,
+            })
+        }
+        Some(_) => {
+            let (label, quoted) = parse_label(chars)?;
+            Ok(Node {
+                label,
+                quoted,
+                children: Vec::new(),
+            })
+        }
+        None => Err(Error::SyntaxTree("The tree is empty".to_string())),
+    }
+}
+
+/// Parse a quoted string, where `\"` escapes a quote, or a word ending at
+/// whitespace or a bracket
+fn parse_label(chars: &mut Peekable<Chars>) -> Result<(String, bool), Error> {
+    let mut label = String::new();
+
+    if chars.peek() == Some(&'"') {
+        chars.next();
+        loop {
+            match chars.next() {
+                Some('\\') => label.extend(chars.next()),
+                Some('"') => return Ok((label, true)),
+                Some(c) => label.push(c),
+                None => {
+                    return Err(Error::SyntaxTree(format!(
+                        "Missing closing quote for '{label}'"
+                    )))
+                }
+            }
+        }
+    }
+
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() || c == '[' || c == ']' {
+            break;
+        }
+        label.push(c);
+        chars.next();
+    }
+
+    if label.is_empty() {
+        return Err(Error::SyntaxTree("A node is missing a label".to_string()));
+    }
+
+    Ok((label, false))
+}
+
+/// Write the tree in the bracket notation of the forest package. Every label
+/// is wrapped in braces so that commas and brackets in it are kept as they are.
+fn to_forest(node: &Node) -> String {
+    let label = if node.quoted {
+        format!(r#"\texttt{{"{}"}}"#, escape_latex(&node.label))
+    } else {
+        escape_latex(&node.label)
+    };
+
+    let children = node
+        .children
+        .iter()
+        .map(|child| format!(" {}", to_forest(child)))
+        .collect::<String>();
+
+    format!("[{{{label}}}{children}]")
+}
+
+/// A node together with its position in the drawing
+struct Placed<'a> {
+    node: &'a Node,
+    /// The horizontal center of the node
+    x: f64,
+    depth: usize,
+    children: Vec<Placed<'a>>,
+}
+
+impl Node {
+    fn text(&self) -> String {
+        if self.quoted {
+            format!("\"{}\"", self.label)
+        } else {
+            self.label.clone()
+        }
+    }
+
+    fn width(&self) -> f64 {
+        self.text().chars().count() as f64 * CHAR_WIDTH + 2.0 * PADDING
+    }
+
+    /// The width of the whole subtree, with siblings placed next to each other
+    fn subtree_width(&self) -> f64 {
+        let children = self.children.iter().map(Node::subtree_width).sum::<f64>()
+            + SIBLING_GAP * self.children.len().saturating_sub(1) as f64;
+        children.max(self.width())
+    }
+
+    /// Place the subtree starting at `left`, with every parent centered above its children
+    fn place(&self, left: f64, depth: usize) -> Placed<'_> {
+        let total = self.subtree_width();
+        let children_width = self.children.iter().map(Node::subtree_width).sum::<f64>()
+            + SIBLING_GAP * self.children.len().saturating_sub(1) as f64;
+
+        // center the children below the parent if the parent is the wider one
+        let mut child_left = left + (total - children_width).max(0.0) / 2.0;
+        let children: Vec<Placed> = self
+            .children
+            .iter()
+            .map(|child| {
+                let placed = child.place(child_left, depth + 1);
+                child_left += child.subtree_width() + SIBLING_GAP;
+                placed
+            })
+            .collect();
+
+        let x = match (children.first(), children.last()) {
+            (Some(first), Some(last)) => (first.x + last.x) / 2.0,
+            _ => left + total / 2.0,
+        };
+
+        Placed {
+            node: self,
+            x,
+            depth,
+            children,
+        }
+    }
+}
+
+fn depth(node: &Node) -> usize {
+    1 + node.children.iter().map(depth).max().unwrap_or(0)
+}
+
+/// Draw the tree as an SVG image
+fn to_svg(tree: &Node) -> String {
+    let width = tree.subtree_width() + 2.0 * PADDING;
+    let height = (depth(tree) - 1) as f64 * LEVEL_HEIGHT + NODE_HEIGHT + 2.0 * PADDING;
+    let placed = tree.place(PADDING, 0);
+
+    let mut svg = String::new();
+    write!(
+        svg,
+        r#"<svg class="syntax-tree" xmlns="http://www.w3.org/2000/svg" viewBox="0 0 {width:.1} {height:.1}" width="{width:.1}" height="{height:.1}" font-family="{FONT}" font-size="13" role="img">"#
+    )
+    .unwrap();
+
+    // draw the edges first so that they end up below the nodes
+    let mut edges = String::new();
+    let mut nodes = String::new();
+    draw(&placed, &mut edges, &mut nodes);
+    svg.push_str(&edges);
+    svg.push_str(&nodes);
+    svg.push_str("</svg>");
+    svg
+}
+
+fn node_top(depth: usize) -> f64 {
+    PADDING + depth as f64 * LEVEL_HEIGHT
+}
+
+fn draw(placed: &Placed, edges: &mut String, nodes: &mut String) {
+    let top = node_top(placed.depth);
+    let node_width = placed.node.width();
+
+    for child in &placed.children {
+        write!(
+            edges,
+            r##"<line x1="{:.1}" y1="{:.1}" x2="{:.1}" y2="{:.1}" stroke="#252525"/>"##,
+            placed.x,
+            top + NODE_HEIGHT,
+            child.x,
+            node_top(child.depth)
+        )
+        .unwrap();
+        draw(child, edges, nodes);
+    }
+
+    // labelled nodes get a box in the primary colour, quoted leaves are plain text
+    if !placed.node.quoted {
+        write!(
+            nodes,
+            r##"<rect x="{:.1}" y="{top:.1}" width="{node_width:.1}" height="{NODE_HEIGHT}" rx="6" fill="#ffffff" stroke="{}" stroke-width="1.5"/>"##,
+            placed.x - node_width / 2.0,
+            PALETTE[0]
+        )
+        .unwrap();
+    }
+    write!(
+        nodes,
+        r#"<text x="{:.1}" y="{:.1}" text-anchor="middle"{}>{}</text>"#,
+        placed.x,
+        top + NODE_HEIGHT / 2.0 + 4.5,
+        if placed.node.quoted {
+            r#" font-family="monospace""#
+        } else {
+            ""
+        },
+        escape_html(&placed.node.text())
+    )
+    .unwrap();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The error message of a tree that cannot be parsed
+    fn error(text: &str) -> String {
+        match parse_tree(text) {
+            Err(Error::SyntaxTree(message)) => message,
+            _ => panic!("'{text}' should not be parsed"),
+        }
+    }
+
+    #[test]
+    fn parses_nested_nodes() {
+        let tree = parse_tree("  [S [NP Alice] [VP [V sees] [NP Bob]]]\n")
+            .ok()
+            .unwrap();
+        assert_eq!(tree.label, "S");
+        assert_eq!(tree.children.len(), 2);
+        assert_eq!(tree.children[1].children[1].children[0].label, "Bob");
+        assert!(!tree.children[0].children[0].quoted);
+    }
+
+    #[test]
+    fn parses_quoted_leaves() {
+        let tree = parse_tree(r#"[Text "say \"hi\" [now]"]"#).ok().unwrap();
+        let leaf = &tree.children[0];
+        assert_eq!(leaf.label, r#"say "hi" [now]"#);
+        assert!(leaf.quoted);
+    }
+
+    #[test]
+    fn parses_a_single_word() {
+        let tree = parse_tree("leaf").ok().unwrap();
+        assert_eq!(tree.label, "leaf");
+        assert!(tree.children.is_empty());
+    }
+
+    #[test]
+    fn rejects_malformed_trees() {
+        assert_eq!(error(""), "The tree is empty");
+        assert_eq!(error("   "), "The tree is empty");
+        assert_eq!(error("[S [NP]"), "Missing closing bracket for 'S'");
+        assert_eq!(error(r#"[S "open]"#), "Missing closing quote for 'open]'");
+        assert_eq!(error("[]"), "A node is missing a label");
+        assert_eq!(
+            error("[S] [T]"),
+            "Unexpected text after the end of the tree"
+        );
+    }
+
+    #[test]
+    fn escapes_labels() {
+        let tree = parse_tree(r#"[a_b "<&>"]"#).ok().unwrap();
+        assert_eq!(to_forest(&tree), r#"[{a\_b} [{\texttt{"<\&>"}}]]"#);
+        assert!(to_svg(&tree).contains("&lt;&amp;&gt;"));
+    }
+}
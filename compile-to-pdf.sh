@@ -4,6 +4,6 @@ mv thesis.tex build/
 cd build
 ln -s ../figures
 ln -s ../include
-pdflatex -synctex=1 -interaction=nonstopmode --shell-escape thesis.tex
+pdflatex -synctex=1 -interaction=nonstopmode thesis.tex
 cd ..
 mv build/thesis.pdf .